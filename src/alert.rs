@@ -0,0 +1,385 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, Cursor, Write};
+use std::path::PathBuf;
+use std::process::Command;
+
+use chrono::Local;
+use log::{info, warn};
+use reqwest::blocking::Client;
+use rodio::{Decoder, OutputStream, Sink};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::config::{config, SoundConfig};
+use crate::discord::DiscordSink;
+
+// 기본 알림 소리
+const ALERT_SOUND: &[u8] = include_bytes!("../alert.wav");
+
+/// 알림을 보낼 곳
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SinkKind {
+    Audio,
+    Desktop,
+    Webhook,
+    Discord,
+    Log,
+    Command,
+}
+
+/// 알림 종류. 종류마다 다른 소리를 설정할 수 있다
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// 처음 뜯긴 아바타
+    NewLeak,
+    /// 이미 뜯겼던 아바타가 다시 올라옴
+    ReLeak,
+    /// 감지 횟수가 높은 유저가 입장함
+    SuspectJoined,
+}
+
+impl Severity {
+    pub fn leak(again: bool) -> Severity {
+        if again { Severity::ReLeak } else { Severity::NewLeak }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::NewLeak => "new_leak",
+            Severity::ReLeak => "re_leak",
+            Severity::SuspectJoined => "suspect_joined",
+        }
+    }
+}
+
+/// 알림 한 건
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    pub severity: Severity,
+    pub title: String,
+    pub message: String,
+    /// 알림이 생긴 시간 (UTC 밀리초)
+    pub time: i64,
+    /// 아바타가 뜯겼을 때의 자세한 정보
+    pub leak: Option<LeakInfo>,
+    /// 의심 유저가 입장했을 때의 자세한 정보
+    pub suspect: Option<SuspectInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LeakInfo {
+    pub avatars: Vec<String>,
+    /// 의심 유저 이름과 감지 횟수
+    pub suspects: Vec<(String, u32)>,
+    /// 뜯겼을 때 있던 인스턴스
+    pub location: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SuspectInfo {
+    pub name: String,
+    pub score: u32,
+    /// 최근 감지 근거 요약
+    pub evidence: Vec<String>,
+    pub location: String,
+}
+
+impl Alert {
+    pub fn new(severity: Severity, title: impl Into<String>, message: impl Into<String>) -> Alert {
+        Alert { severity, title: title.into(), message: message.into(), time: Local::now().timestamp_millis(), leak: None, suspect: None }
+    }
+
+    pub fn with_leak(mut self, leak: LeakInfo) -> Alert {
+        self.leak = Some(leak);
+        self
+    }
+
+    pub fn with_suspect(mut self, suspect: SuspectInfo) -> Alert {
+        self.suspect = Some(suspect);
+        self
+    }
+}
+
+pub trait AlertSink: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn send(&self, alert: &Alert) -> Result<(), String>;
+
+    /// 보내지 못하고 남겨둔 알림 다시 보내기
+    fn flush(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// 소리를 내는 알림인지. 알림 소리를 끄면 건너뛴다
+    fn audible(&self) -> bool {
+        false
+    }
+}
+
+/// 알림 소리를 끝까지 재생. 종류별로 설정한 소리 파일이 없으면 내장된 소리 사용
+pub struct AudioSink {
+    pub sounds: SoundConfig,
+}
+
+impl AudioSink {
+    fn sound_file(&self, severity: Severity) -> Option<&PathBuf> {
+        match severity {
+            Severity::NewLeak => self.sounds.new_leak.as_ref(),
+            Severity::ReLeak => self.sounds.re_leak.as_ref(),
+            Severity::SuspectJoined => self.sounds.suspect_joined.as_ref(),
+        }
+    }
+}
+
+impl AlertSink for AudioSink {
+    fn name(&self) -> &'static str {
+        "audio"
+    }
+
+    fn send(&self, alert: &Alert) -> Result<(), String> {
+        // OutputStream 이 사라지면 소리도 끊기므로 재생이 끝날 때 까지 잡아둔다
+        let (_stream, handle) = OutputStream::try_default().map_err(|e| tr!("alert.audio_device_error", e))?;
+        let sink = Sink::try_new(&handle).map_err(|e| tr!("alert.audio_play_error", e))?;
+        sink.set_volume(self.sounds.volume);
+        match self.sound_file(alert.severity) {
+            Some(path) => {
+                let file = File::open(path).map_err(|e| tr!("alert.sound_open_error", path.display(), e))?;
+                sink.append(Decoder::new(BufReader::new(file)).map_err(|e| tr!("alert.sound_file_decode_error", path.display(), e))?);
+            }
+            None => sink.append(Decoder::new(Cursor::new(ALERT_SOUND)).map_err(|e| tr!("alert.sound_decode_error", e))?),
+        }
+        sink.sleep_until_end();
+        Ok(())
+    }
+
+    fn audible(&self) -> bool {
+        true
+    }
+}
+
+/// 윈도우 알림 센터 알림
+pub struct DesktopSink;
+
+impl AlertSink for DesktopSink {
+    fn name(&self) -> &'static str {
+        "desktop"
+    }
+
+    fn send(&self, alert: &Alert) -> Result<(), String> {
+        let escape = |value: &str| value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('\'', "''");
+        let script = format!(
+            "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null;\
+             [Windows.Data.Xml.Dom.XmlDocument, Windows.Data.Xml.Dom.XmlDocument, ContentType = WindowsRuntime] > $null;\
+             $xml = New-Object Windows.Data.Xml.Dom.XmlDocument;\
+             $xml.LoadXml('<toast><visual><binding template=\"ToastGeneric\"><text>{}</text><text>{}</text></binding></visual></toast>');\
+             [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('Anti-Ripper').Show([Windows.UI.Notifications.ToastNotification]::new($xml))",
+            escape(&alert.title), escape(&alert.message)
+        );
+        let status = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .status()
+            .map_err(|e| tr!("alert.desktop_error", e))?;
+        if status.success() { Ok(()) } else { Err(tr!("alert.desktop_error", status)) }
+    }
+}
+
+/// 설정한 URL 로 알림 내용을 JSON 으로 전송
+pub struct WebhookSink {
+    pub url: String,
+}
+
+impl AlertSink for WebhookSink {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn send(&self, alert: &Alert) -> Result<(), String> {
+        let response = Client::new().post(&self.url).json(alert).send().map_err(|e| tr!("alert.webhook_error", e))?;
+        if response.status().is_success() { Ok(()) } else { Err(tr!("alert.webhook_error", response.status())) }
+    }
+}
+
+/// 파일 끝에 한 줄씩 기록
+pub struct LogSink {
+    pub path: PathBuf,
+}
+
+impl AlertSink for LogSink {
+    fn name(&self) -> &'static str {
+        "log"
+    }
+
+    fn send(&self, alert: &Alert) -> Result<(), String> {
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path).map_err(|e| tr!("alert.log_open_error", self.path.display(), e))?;
+        let line = json!({ "time": Local::now().to_rfc3339(), "severity": alert.severity, "title": alert.title, "message": alert.message });
+        writeln!(file, "{}", line).map_err(|e| tr!("alert.log_write_error", e))
+    }
+}
+
+/// 사용자가 지정한 명령 실행. 알림 내용은 환경 변수로 넘긴다
+pub struct CommandSink {
+    pub command: String,
+}
+
+impl AlertSink for CommandSink {
+    fn name(&self) -> &'static str {
+        "command"
+    }
+
+    fn send(&self, alert: &Alert) -> Result<(), String> {
+        let status = Command::new("cmd")
+            .args(["/C", &self.command])
+            .env("ANTI_RIPPER_ALERT_SEVERITY", alert.severity.as_str())
+            .env("ANTI_RIPPER_ALERT_TITLE", &alert.title)
+            .env("ANTI_RIPPER_ALERT_MESSAGE", &alert.message)
+            .status()
+            .map_err(|e| tr!("alert.command_error", e))?;
+        if status.success() { Ok(()) } else { Err(tr!("alert.command_error", status)) }
+    }
+}
+
+/// 여러 곳에 한꺼번에 알림 보내기
+pub struct Alerter {
+    sinks: Vec<Box<dyn AlertSink>>,
+}
+
+impl Alerter {
+    pub fn new(sinks: Vec<Box<dyn AlertSink>>) -> Alerter {
+        Alerter { sinks }
+    }
+
+    /// 설정에서 고른 알림 방식으로 만들기
+    pub fn from_config() -> Alerter {
+        let alerts = &config().alerts;
+        let sinks = alerts.sinks.iter().map(|kind| -> Box<dyn AlertSink> {
+            match kind {
+                SinkKind::Audio => Box::new(AudioSink { sounds: alerts.sounds.clone() }),
+                SinkKind::Desktop => Box::new(DesktopSink),
+                SinkKind::Webhook => Box::new(WebhookSink { url: alerts.webhook_url.clone() }),
+                SinkKind::Discord => Box::new(DiscordSink::new(alerts.discord_webhook_url.clone())),
+                SinkKind::Log => Box::new(LogSink { path: alerts.log_file.clone().unwrap_or_else(|| config().data_path("alerts.log")) }),
+                SinkKind::Command => Box::new(CommandSink { command: alerts.command.clone() }),
+            }
+        }).collect();
+        Alerter::new(sinks)
+    }
+
+    /// 모든 곳에 보내고, 실패한 곳의 오류를 돌려준다
+    pub fn send(&self, alert: &Alert, muted: bool) -> Vec<String> {
+        info!("alert severity={} message={}", alert.severity.as_str(), alert.message);
+        self.sinks.iter()
+            .filter(|a| !(muted && a.audible()))
+            .filter_map(|a| a.send(alert).err().map(|e| {
+                warn!("alert sink failed sink={} error={}", a.name(), e);
+                format!("[{}] {}", a.name(), e)
+            }))
+            .collect()
+    }
+
+    /// 남겨둔 알림을 다시 보내고, 실패한 곳의 오류를 돌려준다
+    pub fn flush(&self) -> Vec<String> {
+        self.sinks.iter()
+            .filter_map(|a| a.flush().err().map(|e| format!("[{}] {}", a.name(), e)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// 받은 알림을 기록만 하는 곳
+    struct RecordingSink(Mutex<Vec<Alert>>);
+
+    impl RecordingSink {
+        fn new() -> Arc<RecordingSink> {
+            Arc::new(RecordingSink(Mutex::new(vec![])))
+        }
+
+        fn count(&self) -> usize {
+            self.0.lock().unwrap().len()
+        }
+    }
+
+    impl AlertSink for Arc<RecordingSink> {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        fn send(&self, alert: &Alert) -> Result<(), String> {
+            self.0.lock().unwrap().push(alert.clone());
+            Ok(())
+        }
+    }
+
+    /// 소리를 내는 곳처럼 동작하는 기록
+    struct AudibleSink(Arc<RecordingSink>);
+
+    impl AlertSink for AudibleSink {
+        fn name(&self) -> &'static str {
+            "audible"
+        }
+
+        fn send(&self, alert: &Alert) -> Result<(), String> {
+            self.0.send(alert)
+        }
+
+        fn audible(&self) -> bool {
+            true
+        }
+    }
+
+    /// 항상 실패하는 곳
+    struct FailingSink;
+
+    impl AlertSink for FailingSink {
+        fn name(&self) -> &'static str {
+            "failing"
+        }
+
+        fn send(&self, _alert: &Alert) -> Result<(), String> {
+            Err("offline".to_string())
+        }
+    }
+
+    fn alert() -> Alert {
+        Alert::new(Severity::NewLeak, "title", "message")
+    }
+
+    #[test]
+    fn sends_to_every_sink() {
+        let (first, second) = (RecordingSink::new(), RecordingSink::new());
+        let alerter = Alerter::new(vec![Box::new(first.clone()), Box::new(second.clone())]);
+        assert!(alerter.send(&alert(), false).is_empty());
+        assert_eq!(first.count(), 1);
+        assert_eq!(second.count(), 1);
+        assert_eq!(first.0.lock().unwrap()[0].message, "message");
+    }
+
+    #[test]
+    fn mute_skips_audible_sinks() {
+        let (quiet, loud) = (RecordingSink::new(), RecordingSink::new());
+        let alerter = Alerter::new(vec![Box::new(quiet.clone()), Box::new(AudibleSink(loud.clone()))]);
+        alerter.send(&alert(), true);
+        assert_eq!(quiet.count(), 1);
+        assert_eq!(loud.count(), 0);
+
+        alerter.send(&alert(), false);
+        assert_eq!(quiet.count(), 2);
+        assert_eq!(loud.count(), 1);
+    }
+
+    #[test]
+    fn collects_errors_without_stopping() {
+        let (before, after) = (RecordingSink::new(), RecordingSink::new());
+        let alerter = Alerter::new(vec![Box::new(before.clone()), Box::new(FailingSink), Box::new(after.clone()), Box::new(FailingSink)]);
+        let errors = alerter.send(&alert(), false);
+        assert_eq!(errors, vec!["[failing] offline".to_string(); 2]);
+        assert_eq!(before.count(), 1);
+        assert_eq!(after.count(), 1);
+    }
+}
@@ -0,0 +1,170 @@
+use std::fs;
+
+use reqwest::blocking::Client;
+use reqwest::header::{COOKIE, HeaderValue, USER_AGENT};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::config;
+use crate::library::{get_user, now_millis};
+use crate::profile;
+use crate::structs::UserData;
+use crate::PROGRAM_USER_AGENT;
+
+pub const ALLOWLIST_FILE: &str = "allowlist.json";
+// 브챗 친구 목록 API 는 한번에 100명 까지
+const FRIENDS_PAGE: usize = 100;
+
+/// 허용 목록에 들어간 방법
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AllowSource {
+    /// allow 명령으로 직접 추가
+    Manual,
+    /// 브챗 친구 목록에서 가져옴. 다시 가져올 때 친구가 아니면 빠진다
+    Friend,
+}
+
+/// 감지 횟수, 알림, 내보내기에서 빼는 유저. 감지 근거는 그대로 남긴다
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllowEntry {
+    pub user_id: String,
+    /// 추가할 때 알고 있던 이름. user_id.json 에 없는 유저도 이름으로 찾을 수 있게 남긴다
+    #[serde(default)]
+    pub display_name: String,
+    #[serde(default)]
+    pub note: String,
+    /// 추가한 시간 (UTC 밀리초)
+    pub added: i64,
+    pub source: AllowSource,
+}
+
+/// 허용 목록과 이름으로 찾기 위한 user_id.json
+pub struct Allowlist {
+    pub entries: Vec<AllowEntry>,
+    users: Vec<UserData>,
+}
+
+impl Allowlist {
+    pub fn load() -> Allowlist {
+        Allowlist { entries: get_allowlist(), users: get_user() }
+    }
+
+    /// 감지 기록의 이름이 허용된 유저인지. usr_ ID 로 비교하고, ID 를 모르면 저장된 이름으로 비교한다
+    pub fn contains(&self, name: &str) -> bool {
+        if self.entries.is_empty() {
+            return false;
+        }
+        match self.users.iter().find(|a| a.display_name == name) {
+            Some(user) if !user.user_id.is_empty() => self.entries.iter().any(|a| a.user_id == user.user_id),
+            _ => self.entries.iter().any(|a| !a.display_name.is_empty() && a.display_name == name),
+        }
+    }
+}
+
+pub fn is_valid_id(user_id: &str) -> bool {
+    user_id.starts_with("usr_") && user_id.len() > 4
+}
+
+pub fn get_allowlist() -> Vec<AllowEntry> {
+    fs::read_to_string(config().data_path(ALLOWLIST_FILE)).ok()
+        .and_then(|a| serde_json::from_str(&a).ok())
+        .unwrap_or_default()
+}
+
+fn set_allowlist(list: &[AllowEntry]) {
+    let text = serde_json::to_string_pretty(list).expect(&tr!("json.serialize_error"));
+    fs::write(config().data_path(ALLOWLIST_FILE), text).expect(&tr!("io.write_error"));
+}
+
+/// 직접 추가. 이미 있으면 메모만 바꾸고 직접 추가한 것으로 바꾼다
+pub fn add(user_id: &str, note: Option<String>) -> Result<AllowEntry, String> {
+    if !is_valid_id(user_id) {
+        return Err(tr!("allow.invalid_id", user_id));
+    }
+    let display_name = get_user().into_iter().find(|a| a.user_id == user_id).map(|a| a.display_name).unwrap_or_default();
+    let mut list = get_allowlist();
+    let entry = match list.iter_mut().find(|a| a.user_id == user_id) {
+        Some(found) => {
+            if let Some(note) = note {
+                found.note = note;
+            }
+            if found.display_name.is_empty() {
+                found.display_name = display_name;
+            }
+            found.source = AllowSource::Manual;
+            found.clone()
+        }
+        None => {
+            let entry = AllowEntry { user_id: user_id.to_string(), display_name, note: note.unwrap_or_default(), added: now_millis(), source: AllowSource::Manual };
+            list.push(entry.clone());
+            entry
+        }
+    };
+    set_allowlist(&list);
+    Ok(entry)
+}
+
+/// 빼면 true, 목록에 없었으면 false
+pub fn remove(user_id: &str) -> bool {
+    let mut list = get_allowlist();
+    let before = list.len();
+    list.retain(|a| a.user_id != user_id);
+    if list.len() == before {
+        return false;
+    }
+    set_allowlist(&list);
+    true
+}
+
+fn fetch_friends_page(client: &Client, token: &str, offline: bool, offset: usize) -> Result<Vec<Value>, String> {
+    let url = format!("{}?offline={}&n={}&offset={}", config().vrchat_url("auth/user/friends"), offline, FRIENDS_PAGE, offset);
+    let response = client.get(url)
+        .header(USER_AGENT, PROGRAM_USER_AGENT)
+        .header(COOKIE, HeaderValue::from_str(token).map_err(|_| tr!("login.token_convert_error"))?)
+        .send()
+        .map_err(|e| tr!("allow.friends_error", e))?;
+    if !response.status().is_success() {
+        return Err(tr!("allow.friends_error", response.status()));
+    }
+    response.json().map_err(|e| tr!("allow.friends_error", e))
+}
+
+/// 지금 계정의 브챗 친구 목록 (온라인, 오프라인 모두). (usr_ ID, 이름)
+fn fetch_friends() -> Result<Vec<(String, String)>, String> {
+    let token = fs::read_to_string(profile::active_path("auth")).map_err(|_| tr!("login.token_read_error"))?;
+    let client = Client::new();
+    let mut friends: Vec<(String, String)> = vec![];
+    for offline in [false, true] {
+        let mut offset = 0;
+        loop {
+            let page = fetch_friends_page(&client, token.trim(), offline, offset)?;
+            for friend in &page {
+                let (Some(id), Some(name)) = (friend["id"].as_str(), friend["displayName"].as_str()) else { continue };
+                if !friends.iter().any(|(a, _)| a == id) {
+                    friends.push((id.to_string(), name.to_string()));
+                }
+            }
+            if page.len() < FRIENDS_PAGE {
+                break;
+            }
+            offset += FRIENDS_PAGE;
+        }
+    }
+    Ok(friends)
+}
+
+/// 친구 목록으로 친구 항목을 새로 채운다. 직접 추가한 항목은 그대로 둔다. 친구 수를 돌려준다
+pub fn sync_friends() -> Result<usize, String> {
+    let friends = fetch_friends()?;
+    let mut list = get_allowlist();
+    list.retain(|a| a.source == AllowSource::Manual || friends.iter().any(|(id, _)| *id == a.user_id));
+    for (user_id, display_name) in &friends {
+        match list.iter_mut().find(|a| a.user_id == *user_id) {
+            Some(found) => found.display_name = display_name.clone(),
+            None => list.push(AllowEntry { user_id: user_id.clone(), display_name: display_name.clone(), note: String::new(), added: now_millis(), source: AllowSource::Friend }),
+        }
+    }
+    set_allowlist(&list);
+    Ok(friends.len())
+}
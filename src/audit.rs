@@ -0,0 +1,68 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+
+use log::warn;
+use serde::Serialize;
+
+use crate::bundle::JoinRow;
+use crate::config::config;
+use crate::worn::WornInterval;
+
+pub const AUDIT_FILE: &str = "audit.jsonl";
+
+/// 감지 횟수를 올린 규칙
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditRule {
+    /// VRCX 입장/퇴장 기록으로 계산한 같이 있던 시간 중 뜯긴 시간 앞뒤 범위와 가장 많이 겹친 것
+    PresenceInWindow,
+    /// 실시간 감시 중 입장한 뒤 리퍼 스토어 확인에서 새로 뜯긴 아바타가 나옴
+    LiveJoinBeforeLeak,
+}
+
+/// 감지 횟수가 한번 오를 때 마다 남기는 기록
+#[derive(Debug, Serialize)]
+pub struct AuditRecord {
+    /// 기록한 시간 (UTC 밀리초)
+    pub time: i64,
+    pub rule: AuditRule,
+    pub name: String,
+    pub avatar_id: String,
+    pub avatar_name: String,
+    /// 뜯긴 시간 (UTC 밀리초)
+    pub leak_time: i64,
+    /// 입장 기록을 찾은 범위 (UTC 밀리초)
+    pub window_start: i64,
+    pub window_end: i64,
+    /// 사용한 VRCX 입장 기록. 실시간 감시는 없음
+    pub join_row: Option<JoinRow>,
+    /// 사용한 VRCX 퇴장 기록. 퇴장 기록이 없으면 없음
+    pub leave_row: Option<JoinRow>,
+    pub joined_at: i64,
+    pub left_at: Option<i64>,
+    /// 같이 있던 시간이 범위와 겹친 길이 (초)
+    pub overlap: i64,
+    /// 입장 시간 - 뜯긴 시간 (초)
+    pub delta: i64,
+    /// 같은 뜯김으로 감지 횟수가 오른 유저 수
+    pub candidates: usize,
+    /// 올린 뒤의 감지 횟수
+    pub count: u32,
+    /// 입장 당시 입고 있던 아바타 기록. 아바타 변경 기록이 없으면 없음
+    pub worn: Option<WornInterval>,
+}
+
+pub fn audit_path() -> PathBuf {
+    config().data_path(AUDIT_FILE)
+}
+
+/// audit.jsonl 끝에 한 줄 추가. 실패해도 계산은 계속한다
+pub fn record(record: &AuditRecord) {
+    let line = serde_json::to_string(record).expect(&tr!("json.serialize_error"));
+    let result = OpenOptions::new().create(true).append(true).open(audit_path())
+        .and_then(|mut file| writeln!(file, "{}", line));
+    if let Err(e) = result {
+        warn!("audit write failed: {}", e);
+    }
+}
@@ -0,0 +1,93 @@
+use std::fs;
+use std::fs::File;
+
+use crate::library::{now_millis, ripper_time};
+use crate::profile;
+use crate::structs::{AvatarItem, AvatarRecord, Leaked, SaveData};
+
+/// 리퍼 스토어 상세 정보로 지금 시점의 아바타 기록 만들기
+pub fn from_item(ident: &str, item: &AvatarItem) -> AvatarRecord {
+    AvatarRecord {
+        ident: ident.to_string(),
+        name: item.name.clone(),
+        first_seen: now_millis(),
+        created: item.pc.created.map(ripper_time),
+        updates: item.pc.lastUpdated.map(ripper_time).into_iter().collect(),
+        platforms: item.platforms.clone(),
+        unity_version: item.pc.unityVersion.clone(),
+        listed: true,
+    }
+}
+
+/// 저장된 기록이 없으면 None
+pub fn load(profile_name: &str) -> Option<SaveData> {
+    let path = profile::path(profile_name, "save.json");
+    let file = File::open(path).ok()?;
+    Some(serde_json::from_reader(file).expect(&tr!("json.parse_error")))
+}
+
+pub fn save(profile_name: &str, data: &SaveData) {
+    let path = profile::path(profile_name, "save.json");
+    fs::write(path, serde_json::to_string(data).expect(&tr!("json.serialize_error"))).expect(&tr!("json.write_error"));
+}
+
+/// 예전 형식(이름으로 구분)의 기록과 비교. ident 가 없으므로 이번 한번만 사용한다
+fn compare_legacy(previous: &SaveData, current: &[AvatarRecord], leaked: &mut Leaked) {
+    for avatar in current {
+        match previous.avatar_list.iter().find(|a| a.name == avatar.name) {
+            None => leaked.avatars.push(avatar.name.clone()),
+            Some(old) => {
+                let updated = old.updated.map(ripper_time);
+                if avatar.updates.last().is_some() && avatar.updates.last() != updated.as_ref() {
+                    leaked.avatars.push(avatar.name.clone());
+                    leaked.again = true;
+                }
+            }
+        }
+    }
+}
+
+/// 새로 확인한 목록을 저장된 기록과 ident 로 비교해서 새로 뜯기거나 다시 뜯긴 아바타를 찾고 기록을 갱신한다.
+/// 처음 확인할 때는 기록만 남긴다
+pub fn update(profile_name: &str, count: u32, current: Vec<AvatarRecord>) -> Leaked {
+    let mut leaked = Leaked::default();
+    let Some(mut data) = load(profile_name) else {
+        save(profile_name, &SaveData { count, avatars: current, avatar_list: vec![] });
+        return leaked;
+    };
+
+    if data.avatars.is_empty() && !data.avatar_list.is_empty() {
+        compare_legacy(&data, &current, &mut leaked);
+        save(profile_name, &SaveData { count, avatars: current, avatar_list: vec![] });
+        return leaked;
+    }
+
+    for record in data.avatars.iter_mut() {
+        record.listed = current.iter().any(|a| a.ident == record.ident);
+    }
+    for avatar in current {
+        match data.avatars.iter_mut().find(|a| a.ident == avatar.ident) {
+            None => {
+                leaked.avatars.push(avatar.name.clone());
+                data.avatars.push(avatar);
+            }
+            Some(record) => {
+                if let Some(updated) = avatar.updates.last() {
+                    if !record.updates.contains(updated) {
+                        // 또 뜯긴 아바타
+                        record.updates.push(*updated);
+                        leaked.avatars.push(avatar.name.clone());
+                        leaked.again = true;
+                    }
+                }
+                record.name = avatar.name;
+                record.platforms = avatar.platforms;
+                record.unity_version = avatar.unity_version;
+            }
+        }
+    }
+
+    data.count = count;
+    save(profile_name, &data);
+    leaked
+}
@@ -0,0 +1,314 @@
+use std::fs;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use chrono::{Local, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+
+use crate::config::config;
+use crate::history;
+use crate::external::{decode_key, decode_signature};
+use crate::library::{get_ripper, get_user, parse_vrcx_time, vrcx_time};
+use crate::signing::{public_key, sha256_hex, sign, signing_key};
+use crate::structs::LeakEvent;
+
+// 서명하는 BundleBody 의 모양이 바뀌면 올린다
+const BUNDLE_VERSION: u32 = 2;
+// 실시간 감지는 VRCX 기록과 입장 시간이 조금 다를 수 있다
+const JOIN_TOLERANCE: i64 = 5000;
+
+/// 신고용 감지 근거 묶음
+#[derive(Debug)]
+pub struct Bundle {
+    pub body: BundleBody,
+    /// 서명한 `body` 의 JSON. 다시 직렬화하지 않고 이 내용 그대로 서명을 확인한다
+    pub raw_body: Box<RawValue>,
+    /// 서명에 사용한 Ed25519 공개키 (base64)
+    pub public_key: String,
+    /// `raw_body` 에 대한 Ed25519 서명 (base64)
+    pub signature: String,
+}
+
+/// 파일에 저장하는 모양
+#[derive(Serialize, Deserialize)]
+struct BundleFile {
+    body: Box<RawValue>,
+    public_key: String,
+    signature: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleBody {
+    pub version: u32,
+    pub created_at: String,
+    /// 묶음을 만들 때의 timing.leak_window (초)
+    pub leak_window: u64,
+    pub name: String,
+    pub user_id: Option<String>,
+    pub count: u32,
+    pub events: Vec<LeakEvent>,
+    pub join_rows: Vec<JoinRow>,
+    pub log_lines: Vec<LogLine>,
+}
+
+/// VRCX gamelog_join_leave 테이블의 한 줄
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JoinRow {
+    pub created_at: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub display_name: String,
+    pub location: String,
+    pub user_id: String,
+}
+
+/// 브챗 output_log 에서 뽑아낸 줄과 그 해시
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LogLine {
+    pub file: String,
+    pub line: String,
+    pub sha256: String,
+}
+
+/// 뜯긴 시간 범위와 같이 있던 시간을 합친 범위
+fn windows(events: &[LeakEvent], leak_window: u64) -> Vec<(i64, i64)> {
+    let window = leak_window as i64 * 1000;
+    events.iter().map(|a| {
+        let (start, end) = (a.leak_time - window, a.leak_time + window);
+        (start.min(a.joined_at), a.left_at.map_or(end, |left_at| end.max(left_at)))
+    }).collect()
+}
+
+fn query_join_rows(name: &str, events: &[LeakEvent], leak_window: u64) -> Vec<JoinRow> {
+    let conn = history::open();
+    let mut stmt = conn.prepare("SELECT created_at, type, display_name, location, user_id FROM gamelog_join_leave WHERE display_name = ?1 AND created_at BETWEEN ?2 AND ?3 ORDER BY created_at").expect(&tr!("db.query_error"));
+
+    let mut rows: Vec<JoinRow> = vec![];
+    for (start, end) in windows(events, leak_window) {
+        let result = stmt.query_map([name.to_string(), vrcx_time(start), vrcx_time(end)], |row| {
+            Ok(JoinRow {
+                created_at: row.get(0)?,
+                kind: row.get(1)?,
+                display_name: row.get(2)?,
+                location: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+                user_id: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+            })
+        }).expect(&tr!("db.execute_error"));
+        for row in result {
+            let row = row.expect(&tr!("db.row_error"));
+            if !rows.iter().any(|a| a.created_at == row.created_at && a.kind == row.kind) {
+                rows.push(row);
+            }
+        }
+    }
+    rows
+}
+
+/// 브챗 로그 줄의 시간 (현지 시간)
+pub fn log_line_time(line: &str) -> Option<i64> {
+    let time = NaiveDateTime::parse_from_str(line.get(0..19)?, "%Y.%m.%d %H:%M:%S").ok()?;
+    Local.from_local_datetime(&time).earliest().map(|a| a.timestamp_millis())
+}
+
+/// 남아있는 브챗 로그에서 감지 시간 범위 안의 입장/퇴장 줄 찾기
+fn find_log_lines(name: &str, events: &[LeakEvent], leak_window: u64) -> Vec<LogLine> {
+    let ranges = windows(events, leak_window);
+    let Ok(entries) = fs::read_dir(&config().paths.vrchat_log_dir) else { return vec![] };
+    let mut lines = vec![];
+
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if !file_name.contains("output_log") {
+            continue;
+        }
+        let Ok(file) = File::open(entry.path()) else { continue };
+        for line in BufReader::new(file).lines().map_while(Result::ok) {
+            let is_join = line.contains(&format!("OnPlayerJoined {}", name)) || line.contains(&format!("OnPlayerLeft {}", name));
+            if !is_join {
+                continue;
+            }
+            let Some(time) = log_line_time(&line) else { continue };
+            if ranges.iter().any(|(start, end)| time >= *start && time <= *end) {
+                lines.push(LogLine { file: file_name.clone(), sha256: sha256_hex(line.as_bytes()), line });
+            }
+        }
+    }
+    lines
+}
+
+pub fn create(name: &str) -> Result<Bundle, String> {
+    let ripper = get_ripper().into_iter().find(|a| a.name == name).ok_or(tr!("bundle.no_record", name))?;
+    if ripper.events.is_empty() {
+        return Err(tr!("bundle.no_evidence"));
+    }
+    let user_id = get_user().into_iter().find(|a| a.display_name == name).map(|a| a.user_id);
+    let leak_window = config().timing.leak_window;
+
+    let body = BundleBody {
+        version: BUNDLE_VERSION,
+        created_at: Local::now().to_rfc3339(),
+        leak_window,
+        name: name.to_string(),
+        user_id,
+        count: ripper.events.len() as u32,
+        join_rows: query_join_rows(name, &ripper.events, leak_window),
+        log_lines: find_log_lines(name, &ripper.events, leak_window),
+        events: ripper.events,
+    };
+
+    let key = signing_key();
+    let raw_body = serde_json::value::to_raw_value(&body).expect(&tr!("json.serialize_error"));
+    Ok(Bundle { signature: sign(&key, raw_body.get().as_bytes()), public_key: public_key(&key), body, raw_body })
+}
+
+/// 서명과 내용이 서로 맞는지 확인. 문제가 있으면 모두 돌려준다
+pub fn verify(bundle: &Bundle) -> Vec<String> {
+    let mut problems = vec![];
+    let body = &bundle.body;
+
+    match (decode_key(&bundle.public_key), decode_signature(&bundle.signature)) {
+        (Ok(key), Ok(signature)) => {
+            if key.verify_strict(bundle.raw_body.get().as_bytes(), &signature).is_err() {
+                problems.push(tr!("signature.mismatch"));
+            }
+        }
+        (Err(e), _) | (_, Err(e)) => problems.push(e),
+    }
+
+    if body.version != BUNDLE_VERSION {
+        problems.push(tr!("bundle.unsupported_version", body.version));
+    }
+    if body.count as usize != body.events.len() {
+        problems.push(tr!("bundle.count_mismatch", body.count, body.events.len()));
+    }
+
+    // 확인하는 쪽 설정이 아니라 묶음을 만들 때의 범위로 확인한다
+    let window = body.leak_window as i64 * 1000;
+    for (i, event) in body.events.iter().enumerate() {
+        if event.delta != (event.joined_at - event.leak_time) / 1000 {
+            problems.push(tr!("bundle.delta_mismatch", i + 1));
+        }
+        // 같이 있던 시간이 뜯긴 시간 범위와 겹쳐야 한다
+        if event.joined_at > event.leak_time + window || event.left_at.is_some_and(|a| a < event.leak_time - window) {
+            problems.push(tr!("bundle.delta_out_of_window", i + 1));
+        }
+    }
+
+    let ranges = windows(&body.events, body.leak_window);
+    for row in &body.join_rows {
+        if row.display_name != body.name {
+            problems.push(tr!("bundle.foreign_join_row", row.display_name));
+        }
+        match parse_vrcx_time(&row.created_at) {
+            Some(time) if ranges.iter().any(|(start, end)| time >= *start && time <= *end) => {}
+            _ => problems.push(tr!("bundle.join_row_out_of_window", row.created_at)),
+        }
+    }
+
+    // VRCX 기록에서 나온 근거는 같은 시간의 입장 기록이 있어야 한다
+    for (i, event) in body.events.iter().enumerate() {
+        let matched = body.join_rows.iter().any(|row| {
+            row.kind == "OnPlayerJoined" && parse_vrcx_time(&row.created_at).is_some_and(|a| (a - event.joined_at).abs() <= JOIN_TOLERANCE)
+        });
+        if !event.avatar_id.is_empty() && !matched {
+            problems.push(tr!("bundle.missing_join_row", i + 1));
+        }
+    }
+
+    for log in &body.log_lines {
+        if sha256_hex(log.line.as_bytes()) != log.sha256 {
+            problems.push(tr!("bundle.log_hash_mismatch", log.line));
+        }
+        if !log.line.contains(&body.name) {
+            problems.push(tr!("bundle.foreign_log_line", log.line));
+        }
+    }
+
+    problems
+}
+
+pub fn write(bundle: &Bundle, path: &Path) -> Result<(), String> {
+    let file = BundleFile { body: bundle.raw_body.clone(), public_key: bundle.public_key.clone(), signature: bundle.signature.clone() };
+    let text = serde_json::to_string_pretty(&file).expect(&tr!("json.serialize_error"));
+    fs::write(path, text).map_err(|e| tr!("io.write_path_error", path.display(), e))
+}
+
+pub fn read(path: &Path) -> Result<Bundle, String> {
+    let text = fs::read_to_string(path).map_err(|e| tr!("io.read_path_error", path.display(), e))?;
+    parse(&text)
+}
+
+fn parse(text: &str) -> Result<Bundle, String> {
+    let file: BundleFile = serde_json::from_str(text).map_err(|e| {
+        // 이전 버전 묶음은 body 없이 내용이 위에 펼쳐져 있다
+        match serde_json::from_str::<serde_json::Value>(text).ok().and_then(|a| a.get("version")?.as_u64()) {
+            Some(version) => tr!("bundle.unsupported_version", version),
+            None => tr!("bundle.invalid_format", e),
+        }
+    })?;
+    let version = serde_json::from_str::<serde_json::Value>(file.body.get()).ok().and_then(|a| a.get("version")?.as_u64());
+    if let Some(version) = version.filter(|a| *a != BUNDLE_VERSION as u64) {
+        return Err(tr!("bundle.unsupported_version", version));
+    }
+    let body = serde_json::from_str(file.body.get()).map_err(|e| tr!("bundle.invalid_format", e))?;
+    Ok(Bundle { body, raw_body: file.body, public_key: file.public_key, signature: file.signature })
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::SigningKey;
+    use serde_json::json;
+
+    use super::*;
+
+    /// 주어진 body JSON 을 그대로 서명한 묶음 파일 내용
+    fn signed(body: &str) -> String {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        format!(r#"{{"body": {}, "public_key": "{}", "signature": "{}"}}"#, body, public_key(&key), sign(&key, body.as_bytes()))
+    }
+
+    /// 입장이 뜯긴 시간 10분 뒤인 근거 하나
+    fn body(leak_window: u64) -> serde_json::Value {
+        json!({
+            "version": BUNDLE_VERSION,
+            "created_at": "2024-01-01T00:00:00+09:00",
+            "leak_window": leak_window,
+            "name": "ripper",
+            "user_id": null,
+            "count": 1,
+            "events": [{"avatar_name": "avatar", "avatar_id": "", "leak_time": 1_000_000, "joined_at": 1_600_000, "delta": 600, "location": ""}],
+            "join_rows": [],
+            "log_lines": [],
+        })
+    }
+
+    #[test]
+    fn verifies_stored_body_bytes() {
+        // 다른 빌드가 모르는 필드를 넣고 다른 순서로 쓴 body 도 저장된 그대로 확인한다
+        let mut value = body(900);
+        value["events"][0]["extra"] = json!("from another build");
+        let bundle = parse(&signed(&serde_json::to_string_pretty(&value).unwrap())).unwrap();
+        assert!(verify(&bundle).is_empty(), "{:?}", verify(&bundle));
+    }
+
+    #[test]
+    fn rejects_tampered_body() {
+        let text = signed(&body(900).to_string()).replace("\"ripper\"", "\"someone\"");
+        let bundle = parse(&text).unwrap();
+        assert_eq!(verify(&bundle), vec![tr!("signature.mismatch")]);
+    }
+
+    #[test]
+    fn uses_recorded_leak_window() {
+        let bundle = parse(&signed(&body(300).to_string())).unwrap();
+        assert_eq!(verify(&bundle), vec![tr!("bundle.delta_out_of_window", 1)]);
+    }
+
+    #[test]
+    fn rejects_old_version() {
+        let text = r#"{"version": 1, "name": "ripper", "events": [], "public_key": "", "signature": ""}"#;
+        assert_eq!(parse(text).unwrap_err(), tr!("bundle.unsupported_version", 1));
+    }
+}
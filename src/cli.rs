@@ -0,0 +1,217 @@
+use std::env;
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+use clap::{Command, CommandFactory, FromArgMatches, Parser, Subcommand};
+
+use crate::config::Overrides;
+use crate::i18n::Locale;
+use crate::export::Format;
+use crate::significance::Ranking;
+use crate::verdict::VerdictKind;
+
+// 종료 코드
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_NOT_READY: i32 = 3;
+
+// 도움말 문장은 메세지 파일의 cli, cli_arg 에서 command() 로 넣는다
+#[derive(Debug, Parser)]
+#[command(name = "anti-ripper", version)]
+pub struct Cli {
+    #[command(flatten)]
+    pub overrides: Overrides,
+
+    #[arg(long, global = true)]
+    pub no_update: bool,
+
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    Login {
+        #[arg(long)]
+        force: bool,
+    },
+    Logout,
+    Profiles,
+    Scan {
+        #[arg(long)]
+        force: bool,
+    },
+    Correlate {
+        #[arg(long)]
+        force: bool,
+    },
+    IngestLogs {
+        dir: PathBuf,
+    },
+    Watch {
+        #[arg(long)]
+        plain: bool,
+        #[arg(long)]
+        no_input: bool,
+    },
+    Serve {
+        #[arg(long)]
+        port: Option<u16>,
+    },
+    List {
+        #[arg(long, default_value_t = 1)]
+        min: u32,
+        #[arg(long, value_enum)]
+        by: Option<Ranking>,
+    },
+    Worlds {
+        #[arg(long)]
+        instances: bool,
+    },
+    Avatars,
+    Show {
+        user: String,
+    },
+    Export {
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        #[arg(short, long, value_enum)]
+        format: Option<Format>,
+        #[arg(long, default_value_t = 1)]
+        min_score: u32,
+        #[arg(long)]
+        since: Option<NaiveDate>,
+        #[arg(long)]
+        until: Option<NaiveDate>,
+        #[arg(long, requires = "source")]
+        sign: bool,
+        #[arg(long, requires = "sign")]
+        source: Option<String>,
+    },
+    Import {
+        file: PathBuf,
+        #[arg(long)]
+        source: Option<String>,
+        #[arg(long)]
+        allow_unsigned: bool,
+    },
+    External {
+        #[arg(long, default_value_t = 1)]
+        min: u32,
+    },
+    Bundle {
+        user: String,
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    Verify {
+        file: PathBuf,
+    },
+    Verdict {
+        user: String,
+        #[arg(value_enum)]
+        kind: Option<VerdictKind>,
+        #[arg(long)]
+        note: Option<String>,
+        #[arg(long)]
+        date: Option<NaiveDate>,
+        #[arg(long, conflicts_with = "kind")]
+        remove: bool,
+    },
+    Allow {
+        user_id: String,
+        #[arg(long)]
+        note: Option<String>,
+    },
+    Disallow {
+        user_id: String,
+    },
+    Allowlist {
+        #[arg(long)]
+        sync_friends: bool,
+    },
+    Reset {
+        #[arg(long)]
+        scan: bool,
+        #[arg(long)]
+        correlate: bool,
+        #[arg(long)]
+        all: bool,
+    },
+    Update {
+        #[arg(long)]
+        check: bool,
+        #[arg(long)]
+        rollback: bool,
+        #[arg(short, long)]
+        yes: bool,
+    },
+    Uninstall {
+        #[arg(short, long)]
+        yes: bool,
+        #[arg(long)]
+        remove_exe: bool,
+    },
+}
+
+/// 도움말을 만들기 전에 --lang 값만 먼저 읽기
+pub fn lang_arg() -> Option<Locale> {
+    let args: Vec<String> = env::args().collect();
+    args.iter().enumerate().find_map(|(i, a)| match a.strip_prefix("--lang=") {
+        Some(value) => Locale::parse(value),
+        None if a == "--lang" => args.get(i + 1).and_then(|b| Locale::parse(b)),
+        None => None,
+    })
+}
+
+/// 명령 도움말과 옵션 도움말을 지금 언어로 채운 명령줄 정의
+pub fn command() -> Command {
+    let command = Cli::command().about(tr!("cli.about")).long_about(tr!("cli.long_about"));
+    let command = translate_args(command, "");
+    let names: Vec<String> = command.get_subcommands().map(|a| a.get_name().to_string()).collect();
+    names.iter().fold(command, |command, name| {
+        let key = name.replace('-', "_");
+        command.mut_subcommand(name, |a| translate_args(a.about(tr!(&format!("cli.{}", key))), &format!("{}_", key)))
+    })
+}
+
+/// 옵션 도움말은 cli_arg.<명령>_<옵션>. 값 설명이 따로 있는 옵션은 도움말 문장에서 값을 설명한다
+fn translate_args(command: Command, prefix: &str) -> Command {
+    let ids: Vec<String> = command.get_arguments().map(|a| a.get_id().to_string()).collect();
+    ids.iter().fold(command, |command, id| {
+        command.mut_arg(id, |a| {
+            let hide = a.get_possible_values().iter().any(|b| b.get_help().is_some());
+            a.help(tr!(&format!("cli_arg.{}{}", prefix, id))).hide_possible_values(hide)
+        })
+    })
+}
+
+/// 지금 언어의 도움말로 명령줄 읽기
+pub fn parse() -> Cli {
+    Cli::from_arg_matches(&command().get_matches()).unwrap_or_else(|e| e.exit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::text;
+
+    #[test]
+    fn help_in_catalog() {
+        let command = Cli::command();
+        let mut keys = vec!["cli.about".to_string(), "cli.long_about".to_string()];
+        keys.extend(command.get_arguments().map(|a| format!("cli_arg.{}", a.get_id())));
+        for sub in command.get_subcommands() {
+            let name = sub.get_name().replace('-', "_");
+            keys.push(format!("cli.{}", name));
+            keys.extend(sub.get_arguments().map(|a| format!("cli_arg.{}_{}", name, a.get_id())));
+        }
+        let missing: Vec<&String> = keys.iter().filter(|a| text(a) == **a).collect();
+        assert!(missing.is_empty(), "{:?}", missing);
+        super::command().debug_assert();
+    }
+}
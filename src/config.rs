@@ -0,0 +1,520 @@
+use std::{env, fs};
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use clap::Args;
+use dirs::{config_dir, home_dir};
+use log::LevelFilter;
+use serde::{Deserialize, Serialize};
+
+use crate::alert::SinkKind;
+use crate::i18n::{ENV_LANG, Locale};
+use crate::significance::Ranking;
+use crate::update::Channel;
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+// 이번 실행에서 이미 목록 파일에 적은 이름
+static RECORDED: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/// 데이터 폴더에 만든 파일 이름 목록. uninstall 과 reset --all 은 이 목록에 있는 파일만 지운다
+pub const MANIFEST_FILE: &str = "files.txt";
+
+// 환경 변수 이름
+const ENV_CONFIG: &str = "ANTI_RIPPER_CONFIG";
+const ENV_VRCX_DIR: &str = "ANTI_RIPPER_VRCX_DIR";
+const ENV_DATA_DIR: &str = "ANTI_RIPPER_DATA_DIR";
+const ENV_LOG_DIR: &str = "ANTI_RIPPER_LOG_DIR";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// 화면에 출력하는 언어 (ko, en). 비어 있으면 시스템 언어 사용
+    pub locale: Option<Locale>,
+    pub paths: PathConfig,
+    pub urls: UrlConfig,
+    pub timing: TimingConfig,
+    pub alerts: AlertConfig,
+    pub update: UpdateConfig,
+    pub serve: ServeConfig,
+    pub logging: LoggingConfig,
+    pub scoring: ScoringConfig,
+    pub allowlist: AllowlistConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PathConfig {
+    /// VRCX 설치 폴더 (VRCX.sqlite3 가 있는 곳)
+    pub vrcx_dir: PathBuf,
+    /// 프로그램 데이터 폴더. 비어 있으면 `vrcx_dir/Anti-Ripper` 사용
+    pub data_dir: Option<PathBuf>,
+    /// 브챗 output_log 폴더
+    pub vrchat_log_dir: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UrlConfig {
+    pub vrchat_api: String,
+    pub ripper_api: String,
+    /// 업데이트 확인에 사용하는 릴리즈 목록 주소 (GitHub releases API 형식)
+    pub github_release: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimingConfig {
+    /// 브챗 서버에 한번에 검색할 유저 수
+    pub bulk_size: u64,
+    /// 브챗 API 제한이 걸렸을 때 대기 시간 (초)
+    pub rate_limit_cooldown: u64,
+    /// 유저 입장 후 리퍼 스토어를 확인하기 까지 대기 시간 (초)
+    pub join_check_delay: u64,
+    /// 뜯긴 시간 기준 앞뒤로 검색할 범위 (초)
+    pub leak_window: u64,
+    /// 뜯긴 시간 범위 앞뒤로 입장/퇴장 기록을 더 읽는 시간. 범위 전에 들어와 있던 유저를 찾는다 (초)
+    pub presence_lookback: u64,
+    /// 뜯긴 시간 범위 안에 최소한 이만큼 같이 있어야 감지 횟수를 부여 (초)
+    pub min_overlap: u64,
+    /// 브챗 실행 여부 확인 간격 (초)
+    pub process_poll: u64,
+    /// 브챗 로그 파일이 생길 때 까지 기다리는 시간 (초)
+    pub log_wait: u64,
+    /// 보내지 못한 알림을 다시 보내는 간격 (초)
+    pub alert_retry: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertConfig {
+    /// 알림을 보낼 곳. audio, desktop, webhook, discord, log, command 중에서 고른다
+    pub sinks: Vec<SinkKind>,
+    /// webhook 알림을 받을 URL
+    pub webhook_url: String,
+    /// discord 알림을 보낼 디스코드 웹훅 URL
+    pub discord_webhook_url: String,
+    /// log 알림을 기록할 파일. 비어 있으면 데이터 폴더의 alerts.log
+    pub log_file: Option<PathBuf>,
+    /// command 알림으로 실행할 명령. 알림 내용은 ANTI_RIPPER_ALERT_SEVERITY, ANTI_RIPPER_ALERT_TITLE, ANTI_RIPPER_ALERT_MESSAGE 환경 변수로 전달
+    pub command: String,
+    /// 감지 횟수가 이 값 이상인 유저가 입장하면 바로 알림. 0 이면 사용 안함
+    pub suspect_threshold: u32,
+    pub sounds: SoundConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpdateConfig {
+    /// 하위 명령 없이 실행할 때 새 버전 확인
+    pub check_on_start: bool,
+    /// stable 은 정식 버전만, beta 는 시험 버전도 받는다
+    pub channel: Channel,
+    /// 릴리즈에서 받을 실행 파일 이름. `<이름>.sha256` 또는 SHA256SUMS 체크섬 파일이 같이 있어야 한다
+    pub asset_name: String,
+    /// 릴리즈 서명 확인용 Ed25519 공개키 (base64). 비어 있으면 업데이트를 설치하지 않는다. `<이름>.sig` 서명 파일이 있어야 한다
+    pub public_key: String,
+}
+
+/// serve 명령 설정
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServeConfig {
+    /// 제어 API 를 열 주소. 다른 컴퓨터에서 접속할 수 없도록 기본값은 127.0.0.1
+    pub bind: String,
+    pub port: u16,
+    /// 있으면 모든 요청에 `Authorization: Bearer <token>` 이 있어야 한다. 로컬 주소가 아니면 꼭 필요
+    pub token: String,
+    /// 입장과 상관없이 리퍼 스토어를 다시 확인하는 간격 (초). 0 이면 사용 안함
+    pub poll_interval: u64,
+    /// 감시 기록 파일. 비어 있으면 데이터 폴더의 serve.log
+    pub log_file: Option<PathBuf>,
+    /// /events 로 돌려줄 수 있는 최근 이벤트 개수
+    pub max_events: usize,
+}
+
+/// 의심 유저 목록 설정
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringConfig {
+    /// list 명령의 기본 정렬. count 는 감지 횟수, significance 는 평소 같이 있던 비율과 비교한 p-value
+    pub ranking: Ranking,
+}
+
+/// 감지 횟수, 알림, 내보내기에서 빼는 유저 목록 설정
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AllowlistConfig {
+    /// 리퍼 스토어 계산 전에 브챗 친구 목록을 허용 목록으로 가져오기
+    pub sync_friends: bool,
+}
+
+/// 데이터 폴더의 anti-ripper.log 기록 설정
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// error, warn, info, debug, trace 중 하나. 고른 단계보다 중요한 기록만 남긴다
+    pub level: String,
+    /// 기록 파일이 이 크기(KB)를 넘으면 anti-ripper.log.1 로 밀어낸다
+    pub max_size_kb: u64,
+    /// 남겨둘 이전 기록 파일 개수
+    pub max_files: u32,
+}
+
+/// audio 알림 소리 설정
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundConfig {
+    /// 0.0 ~ 2.0. 1.0 이 원래 크기
+    pub volume: f32,
+    /// 처음 뜯긴 아바타 알림 소리 파일. 비어 있으면 내장된 소리 사용
+    pub new_leak: Option<PathBuf>,
+    /// 이미 뜯겼던 아바타가 다시 올라왔을 때 알림 소리 파일
+    pub re_leak: Option<PathBuf>,
+    /// 의심 유저가 입장했을 때 알림 소리 파일
+    pub suspect_joined: Option<PathBuf>,
+}
+
+impl Default for PathConfig {
+    fn default() -> Self {
+        PathConfig {
+            vrcx_dir: config_dir().unwrap_or_default().join("VRCX"),
+            data_dir: None,
+            vrchat_log_dir: home_dir().unwrap_or_default().join("AppData\\LocalLow\\VRChat\\VRChat"),
+        }
+    }
+}
+
+impl Default for UrlConfig {
+    fn default() -> Self {
+        UrlConfig {
+            vrchat_api: "https://api.vrchat.cloud/api/1".to_string(),
+            ripper_api: "https://api.ripper.store/api/v2".to_string(),
+            github_release: "https://api.github.com/repos/kieaer/Anti-ripper/releases".to_string(),
+        }
+    }
+}
+
+impl Default for TimingConfig {
+    fn default() -> Self {
+        TimingConfig {
+            bulk_size: 8,
+            rate_limit_cooldown: 305,
+            join_check_delay: 150,
+            leak_window: 300,
+            presence_lookback: 21600,
+            min_overlap: 30,
+            process_poll: 60,
+            log_wait: 300,
+            alert_retry: 60,
+        }
+    }
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        AlertConfig {
+            sinks: vec![SinkKind::Audio],
+            webhook_url: String::new(),
+            discord_webhook_url: String::new(),
+            log_file: None,
+            command: String::new(),
+            suspect_threshold: 3,
+            sounds: SoundConfig::default(),
+        }
+    }
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        UpdateConfig {
+            check_on_start: true,
+            channel: Channel::Stable,
+            asset_name: "Anti-Ripper.exe".to_string(),
+            public_key: String::new(),
+        }
+    }
+}
+
+impl Default for ServeConfig {
+    fn default() -> Self {
+        ServeConfig {
+            bind: "127.0.0.1".to_string(),
+            port: 17380,
+            token: String::new(),
+            poll_interval: 600,
+            log_file: None,
+            max_events: 200,
+        }
+    }
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        ScoringConfig {
+            ranking: Ranking::Count,
+        }
+    }
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        LoggingConfig {
+            level: "info".to_string(),
+            max_size_kb: 1024,
+            max_files: 5,
+        }
+    }
+}
+
+impl Default for SoundConfig {
+    fn default() -> Self {
+        SoundConfig {
+            volume: 1.0,
+            new_leak: None,
+            re_leak: None,
+            suspect_joined: None,
+        }
+    }
+}
+
+/// 명령줄에서 직접 지정한 설정 값
+#[derive(Debug, Default, Args)]
+pub struct Overrides {
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+    #[arg(long, global = true)]
+    pub vrcx_dir: Option<PathBuf>,
+    #[arg(long, global = true)]
+    pub data_dir: Option<PathBuf>,
+    #[arg(long, global = true)]
+    pub log_dir: Option<PathBuf>,
+    #[arg(long, global = true, value_enum)]
+    pub lang: Option<Locale>,
+}
+
+impl Config {
+    pub fn data_dir(&self) -> PathBuf {
+        self.paths.data_dir.clone().unwrap_or_else(|| self.paths.vrcx_dir.join("Anti-Ripper"))
+    }
+
+    /// 데이터 폴더 안의 파일 경로. 처음 쓰는 이름이면 목록 파일에 남긴다
+    pub fn data_path(&self, name: &str) -> PathBuf {
+        let dir = self.data_dir();
+        record_data_file(&dir, name);
+        dir.join(name)
+    }
+
+    /// 목록 파일에 남은 데이터 파일 이름
+    pub fn data_files(&self) -> Vec<String> {
+        read_manifest(&self.data_dir())
+    }
+
+    pub fn vrcx_database(&self) -> PathBuf {
+        self.paths.vrcx_dir.join("VRCX.sqlite3")
+    }
+
+    pub fn vrchat_url(&self, path: &str) -> String {
+        format!("{}/{}", self.urls.vrchat_api.trim_end_matches('/'), path)
+    }
+
+    pub fn ripper_url(&self, path: &str) -> String {
+        format!("{}/{}", self.urls.ripper_api.trim_end_matches('/'), path)
+    }
+
+    /// 뜯긴 시간 기준 검색 범위 (밀리초)
+    pub fn leak_window_millis(&self) -> u64 {
+        self.timing.leak_window * 1000
+    }
+
+    pub fn presence_lookback_millis(&self) -> u64 {
+        self.timing.presence_lookback * 1000
+    }
+
+    pub fn min_overlap_millis(&self) -> u64 {
+        self.timing.min_overlap * 1000
+    }
+
+    fn apply_env(&mut self) {
+        if let Ok(value) = env::var(ENV_VRCX_DIR) {
+            self.paths.vrcx_dir = PathBuf::from(value);
+        }
+        if let Ok(value) = env::var(ENV_DATA_DIR) {
+            self.paths.data_dir = Some(PathBuf::from(value));
+        }
+        if let Ok(value) = env::var(ENV_LOG_DIR) {
+            self.paths.vrchat_log_dir = PathBuf::from(value);
+        }
+        if let Some(value) = env::var(ENV_LANG).ok().and_then(|a| Locale::parse(&a)) {
+            self.locale = Some(value);
+        }
+    }
+
+    fn apply_overrides(&mut self, overrides: &Overrides) {
+        if let Some(value) = &overrides.vrcx_dir {
+            self.paths.vrcx_dir = value.clone();
+        }
+        if let Some(value) = &overrides.data_dir {
+            self.paths.data_dir = Some(value.clone());
+        }
+        if let Some(value) = &overrides.log_dir {
+            self.paths.vrchat_log_dir = value.clone();
+        }
+        if let Some(value) = overrides.lang {
+            self.locale = Some(value);
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = vec![];
+
+        if self.paths.vrcx_dir.as_os_str().is_empty() {
+            errors.push(tr!("config.vrcx_dir_empty"));
+        }
+        for (key, url) in [("urls.vrchat_api", &self.urls.vrchat_api), ("urls.ripper_api", &self.urls.ripper_api), ("urls.github_release", &self.urls.github_release)] {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                errors.push(tr!("config.invalid_url", key, url));
+            }
+        }
+        // 브챗 유저 검색 API 는 한번에 100명 까지만 허용
+        if self.timing.bulk_size == 0 || self.timing.bulk_size > 100 {
+            errors.push(tr!("config.bulk_size_range", self.timing.bulk_size));
+        }
+        for (key, value) in [("timing.rate_limit_cooldown", self.timing.rate_limit_cooldown), ("timing.leak_window", self.timing.leak_window), ("timing.process_poll", self.timing.process_poll), ("timing.log_wait", self.timing.log_wait), ("timing.alert_retry", self.timing.alert_retry)] {
+            if value == 0 {
+                errors.push(tr!("config.must_be_positive", key));
+            }
+        }
+
+        if self.alerts.sinks.contains(&SinkKind::Webhook) && !self.alerts.webhook_url.starts_with("http://") && !self.alerts.webhook_url.starts_with("https://") {
+            errors.push(tr!("config.invalid_webhook_url", self.alerts.webhook_url));
+        }
+        if self.alerts.sinks.contains(&SinkKind::Discord) && !self.alerts.discord_webhook_url.starts_with("http://") && !self.alerts.discord_webhook_url.starts_with("https://") {
+            errors.push(tr!("config.invalid_discord_url", self.alerts.discord_webhook_url));
+        }
+        if !(0.0..=2.0).contains(&self.alerts.sounds.volume) {
+            errors.push(tr!("config.volume_range", self.alerts.sounds.volume));
+        }
+        for (key, path) in [("alerts.sounds.new_leak", &self.alerts.sounds.new_leak), ("alerts.sounds.re_leak", &self.alerts.sounds.re_leak), ("alerts.sounds.suspect_joined", &self.alerts.sounds.suspect_joined)] {
+            if let Some(path) = path.as_ref().filter(|a| !a.exists()) {
+                errors.push(tr!("config.file_missing", key, path.display()));
+            }
+        }
+        if self.alerts.sinks.contains(&SinkKind::Command) && self.alerts.command.trim().is_empty() {
+            errors.push(tr!("config.command_empty"));
+        }
+
+        match self.serve.bind.parse::<IpAddr>() {
+            Err(_) => errors.push(tr!("config.invalid_bind", self.serve.bind)),
+            // 제어 API 는 일시 정지와 의심 유저 목록을 다루므로 네트워크에 열 때는 토큰이 있어야 한다
+            Ok(bind) if !bind.is_loopback() && self.serve.token.trim().is_empty() => errors.push(tr!("config.bind_needs_token", self.serve.bind)),
+            Ok(_) => {}
+        }
+        if self.serve.max_events == 0 {
+            errors.push(tr!("config.must_be_positive", "serve.max_events"));
+        }
+
+        if self.logging.level.parse::<LevelFilter>().is_err() {
+            errors.push(tr!("config.invalid_log_level", self.logging.level));
+        }
+        if self.logging.max_size_kb == 0 {
+            errors.push(tr!("config.must_be_positive", "logging.max_size_kb"));
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+/// 설정 파일 위치. 명령줄 > 환경 변수 > 데이터 폴더 순서로 찾는다.
+fn config_path(overrides: &Overrides) -> PathBuf {
+    if let Some(path) = &overrides.config {
+        return path.clone();
+    }
+    if let Ok(path) = env::var(ENV_CONFIG) {
+        return PathBuf::from(path);
+    }
+
+    let mut base = Config::default();
+    base.apply_env();
+    base.apply_overrides(overrides);
+    base.data_path("config.toml")
+}
+
+/// 기본값 < config.toml < 환경 변수 < 명령줄 순서로 설정을 불러온다.
+pub fn load(overrides: &Overrides) -> Result<Config, Vec<String>> {
+    let path = config_path(overrides);
+
+    let mut config: Config = if path.exists() {
+        let text = fs::read_to_string(&path).map_err(|e| vec![tr!("config.read_error", path.display(), e)])?;
+        toml::from_str(&text).map_err(|e| vec![tr!("config.parse_error", path.display(), e)])?
+    } else {
+        let config = Config::default();
+        // 처음 실행할 때 기본 설정 파일 만들어 두기
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let _ = fs::write(&path, toml::to_string_pretty(&config).unwrap_or_default());
+        config
+    };
+
+    config.apply_env();
+    config.apply_overrides(overrides);
+    config.validate()?;
+
+    Ok(config)
+}
+
+pub fn init(overrides: &Overrides) -> Result<(), Vec<String>> {
+    let config = load(overrides)?;
+    CONFIG.set(config).map_err(|_| vec![tr!("config.already_initialized")])
+}
+
+pub fn config() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+fn read_manifest(dir: &Path) -> Vec<String> {
+    fs::read_to_string(dir.join(MANIFEST_FILE))
+        .map(|a| a.lines().map(|b| b.trim().to_string()).filter(|b| !b.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// 목록 파일에 한 줄 추가. 데이터 폴더가 아직 없어서 적지 못했으면 다음에 다시 적는다
+fn record_data_file(dir: &Path, name: &str) {
+    if name == MANIFEST_FILE {
+        return;
+    }
+    let mut recorded = RECORDED.lock().unwrap_or_else(|e| e.into_inner());
+    let recorded = recorded.get_or_insert_with(|| read_manifest(dir).into_iter().collect());
+    if recorded.contains(name) {
+        return;
+    }
+    let written = OpenOptions::new().create(true).append(true).open(dir.join(MANIFEST_FILE))
+        .and_then(|mut a| writeln!(a, "{}", name));
+    if written.is_ok() {
+        recorded.insert(name.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(bind: &str, token: &str) -> Vec<String> {
+        let mut config = Config::default();
+        config.serve.bind = bind.to_string();
+        config.serve.token = token.to_string();
+        config.validate().err().unwrap_or_default()
+    }
+
+    #[test]
+    fn network_bind_needs_token() {
+        let needs_token = tr!("config.bind_needs_token", "0.0.0.0");
+        assert!(errors("0.0.0.0", "").contains(&needs_token));
+        assert!(!errors("0.0.0.0", "secret").contains(&needs_token));
+        assert!(!errors("127.0.0.1", "").iter().any(|a| a.contains("serve.")));
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use log::{debug, info};
+
+use crate::audit;
+use crate::audit::{AuditRecord, AuditRule};
+use crate::config::config;
+use crate::history;
+use crate::library::{get_ripper, now_millis, set_ripper};
+use crate::presence;
+use crate::presence::Presence;
+use crate::structs::{LeakEvent, RipperData};
+use crate::worlds;
+use crate::worlds::{LeakRecord, Visit};
+use crate::worn;
+use crate::worn::WornInterval;
+
+/// 유저에게 감지 근거를 추가하고 새 감지 횟수를 돌려준다. 같은 뜯김은 두번 세지 않는다.
+pub fn add_event(list: &mut Vec<RipperData>, name: &str, event: LeakEvent) -> u32 {
+    match list.iter_mut().find(|a| a.name == name) {
+        Some(ripper) => {
+            let duplicated = ripper.events.iter().any(|a| a.avatar_id == event.avatar_id && a.avatar_name == event.avatar_name && a.leak_time == event.leak_time);
+            if !duplicated {
+                ripper.count += 1;
+                ripper.events.push(event);
+            }
+            ripper.count
+        }
+        None => {
+            list.push(RipperData { name: name.to_string(), count: 1, events: vec![event], verdict: None });
+            1
+        }
+    }
+}
+
+/// 같이 있던 시간(입장부터 퇴장까지)과 겹치게 아바타를 입고 있었던 기록. 기록이 전혀 없으면 Some(None) 으로 거르지 않는다
+fn worn_while_present(worn: &Option<Vec<WornInterval>>, joined_at: i64, left_at: i64) -> Option<Option<WornInterval>> {
+    match worn {
+        None => Some(None),
+        Some(list) => list.iter().find(|a| a.overlaps(joined_at, left_at)).cloned().map(Some),
+    }
+}
+
+fn count_of(list: &[RipperData], name: &str) -> u32 {
+    list.iter().find(|a| a.name == name).map_or(0, |a| a.count)
+}
+
+/// 뜯긴 시간 앞뒤 leak_window 동안 같이 있던 유저에게 감지 횟수 부여
+pub fn credit_leak(avatar_id: &str, avatar_name: &str, leak_time: i64) {
+    let window = config().leak_window_millis() as i64;
+    let (window_start, window_end) = (leak_time - window, leak_time + window);
+    // 범위 안에서 해당 아바타를 입고 있지 않았다면 그 동안 들어온 유저는 뜯을 수 없다
+    let worn = worn::worn_during(avatar_name, window_start, window_end);
+    if worn.as_ref().is_some_and(|a| a.is_empty()) {
+        debug!("avatar not worn in window avatar_id={} avatar_name={} leak_time={}", avatar_id, avatar_name, leak_time);
+        return;
+    }
+
+    let conn = history::open();
+    let presences = presence::intervals(&conn, window_start, window_end, config().presence_lookback_millis() as i64);
+    let visit = worlds::visit_at(&conn, leak_time);
+
+    // 범위 안에서 여러번 들어왔으면 범위와 가장 많이 겹친 기록만 사용
+    let min_overlap = config().min_overlap_millis() as i64;
+    let mut best: HashMap<String, (LeakEvent, Presence, Option<WornInterval>)> = HashMap::new();
+    for presence in presences {
+        let overlap = presence.overlap(window_start, window_end);
+        if overlap < min_overlap {
+            continue;
+        }
+        // 아바타를 입고 있던 동안 같이 있지 않았던 유저는 제외
+        let Some(worn_interval) = worn_while_present(&worn, presence.joined_at, presence.left_at.unwrap_or(window_end)) else { continue };
+        let event = LeakEvent {
+            avatar_name: avatar_name.to_string(),
+            avatar_id: avatar_id.to_string(),
+            leak_time,
+            joined_at: presence.joined_at,
+            delta: (presence.joined_at - leak_time) / 1000,
+            location: presence.location.clone(),
+            left_at: presence.left_at,
+            overlap: overlap / 1000,
+            world_name: visit.as_ref().filter(|a| a.location == presence.location).map(|a| a.world_name.clone()).unwrap_or_default(),
+        };
+        let replace = best.get(&presence.display_name).is_none_or(|(a, _, _)| a.overlap < event.overlap);
+        if replace {
+            best.insert(presence.display_name.clone(), (event, presence, worn_interval));
+        }
+    }
+
+    // 뜯긴 시간에 내가 있던 인스턴스와 거기 같이 있던 유저. 인스턴스 기록이 없으면 모두
+    let mut players: Vec<String> = best.iter()
+        .filter(|(_, (_, presence, _))| visit.as_ref().is_none_or(|a| a.location == presence.location))
+        .map(|(name, _)| name.clone())
+        .collect();
+    players.sort();
+    worlds::record_leak(LeakRecord {
+        avatar_id: avatar_id.to_string(),
+        avatar_name: avatar_name.to_string(),
+        leak_time,
+        visit,
+        players,
+    });
+
+    if best.is_empty() {
+        debug!("no presence in window avatar_id={} leak_time={}", avatar_id, leak_time);
+        return;
+    }
+
+    let mut ripper_json = get_ripper();
+    let mut records = vec![];
+    for (name, (event, presence, worn_interval)) in best {
+        let before = count_of(&ripper_json, &name);
+        let (joined_at, left_at, delta, overlap) = (event.joined_at, event.left_at, event.delta, event.overlap);
+        let count = add_event(&mut ripper_json, &name, event);
+        if count == before {
+            debug!("duplicate leak skipped name={} avatar_id={} leak_time={}", name, avatar_id, leak_time);
+            continue;
+        }
+        info!("score increased rule=presence_in_window name={} avatar_id={} leak_time={} delta={} overlap={} count={}", name, avatar_id, leak_time, delta, overlap, count);
+        records.push(AuditRecord {
+            time: now_millis(),
+            rule: AuditRule::PresenceInWindow,
+            name,
+            avatar_id: avatar_id.to_string(),
+            avatar_name: avatar_name.to_string(),
+            leak_time,
+            window_start,
+            window_end,
+            join_row: Some(presence.join_row),
+            leave_row: presence.leave_row,
+            joined_at,
+            left_at,
+            overlap,
+            delta,
+            candidates: 0,
+            count,
+            worn: worn_interval,
+        });
+    }
+    set_ripper(ripper_json);
+
+    // 중복으로 건너뛴 유저는 빼고, 실제로 감지 횟수가 오른 유저 수를 기록
+    let candidates = records.len();
+    for mut record in records {
+        record.candidates = candidates;
+        audit::record(&record);
+    }
+}
+
+/// 실시간 감시 중 유저가 입장한 뒤 아바타가 뜯겼을 때 감지 횟수 부여
+pub fn credit_live(name: &str, avatar_names: &[String], joined_at: i64, location: &str) -> u32 {
+    let leak_time = now_millis();
+    let mut ripper_json = get_ripper();
+    let mut count = count_of(&ripper_json, name);
+    for avatar_name in avatar_names {
+        let worn = worn::worn_during(avatar_name, joined_at, leak_time);
+        let Some(worn_interval) = worn_while_present(&worn, joined_at, leak_time) else {
+            debug!("avatar not worn while present name={} avatar_name={}", name, avatar_name);
+            continue;
+        };
+        let before = count;
+        let delta = (joined_at - leak_time) / 1000;
+        count = add_event(&mut ripper_json, name, LeakEvent {
+            avatar_name: avatar_name.clone(),
+            avatar_id: String::new(),
+            leak_time,
+            joined_at,
+            delta,
+            location: location.to_string(),
+            left_at: None,
+            overlap: (leak_time - joined_at) / 1000,
+            world_name: String::new(),
+        });
+        worlds::record_leak(LeakRecord {
+            avatar_id: String::new(),
+            avatar_name: avatar_name.clone(),
+            leak_time,
+            visit: (!location.is_empty()).then(|| Visit::from_location(location)),
+            players: vec![name.to_string()],
+        });
+        if count == before {
+            continue;
+        }
+        info!("score increased rule=live_join_before_leak name={} avatar_name={} delta={} count={}", name, avatar_name, delta, count);
+        audit::record(&AuditRecord {
+            time: leak_time,
+            rule: AuditRule::LiveJoinBeforeLeak,
+            name: name.to_string(),
+            avatar_id: String::new(),
+            avatar_name: avatar_name.clone(),
+            leak_time,
+            window_start: joined_at,
+            window_end: leak_time,
+            join_row: None,
+            leave_row: None,
+            joined_at,
+            left_at: None,
+            overlap: (leak_time - joined_at) / 1000,
+            delta,
+            candidates: 1,
+            count,
+            worn: worn_interval,
+        });
+    }
+    set_ripper(ripper_json);
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn worn(start: i64, end: Option<i64>) -> Option<Vec<WornInterval>> {
+        Some(vec![WornInterval { avatar_name: "avatar".to_string(), start, end }])
+    }
+
+    #[test]
+    fn credits_only_while_worn() {
+        // 아바타를 입기 전에 들어왔다 나간 유저는 제외
+        assert!(worn_while_present(&worn(1000, Some(2000)), 100, 500).is_none());
+        // 입고 있던 중에 들어왔거나, 입기 전에 들어와서 입은 뒤까지 있던 유저
+        assert!(worn_while_present(&worn(1000, Some(2000)), 1500, 3000).is_some());
+        assert!(worn_while_present(&worn(1000, None), 500, 1200).is_some());
+        // 벗은 뒤에 들어온 유저는 제외
+        assert!(worn_while_present(&worn(1000, Some(2000)), 2500, 3000).is_none());
+        // 입고 있던 기록이 없으면 거르지 않는다
+        assert_eq!(worn_while_present(&None, 100, 500), Some(None));
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::io;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap};
+
+use crate::describe_user;
+use crate::allowlist::Allowlist;
+use crate::library::get_ripper;
+use crate::structs::RipperData;
+use crate::watch::{self, WatchControl, WatchEvent};
+
+// 화면에 남겨둘 최대 줄 수
+const MAX_JOINS: usize = 50;
+const MAX_LOG: usize = 200;
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(PartialEq)]
+enum Focus {
+    Roster,
+    Leaderboard,
+}
+
+struct Dashboard {
+    control: WatchControl,
+    roster: Vec<String>,
+    joins: VecDeque<String>,
+    pending: BTreeMap<String, String>,
+    leaderboard: Vec<RipperData>,
+    log: VecDeque<String>,
+    focus: Focus,
+    roster_state: ListState,
+    board_state: ListState,
+    popup: Option<(String, Vec<String>)>,
+    last_refresh: Instant,
+}
+
+fn now() -> String {
+    Local::now().format("%H:%M:%S").to_string()
+}
+
+impl Dashboard {
+    fn new(control: WatchControl) -> Dashboard {
+        let mut dashboard = Dashboard {
+            control,
+            roster: vec![],
+            joins: VecDeque::new(),
+            pending: BTreeMap::new(),
+            leaderboard: vec![],
+            log: VecDeque::new(),
+            focus: Focus::Leaderboard,
+            roster_state: ListState::default(),
+            board_state: ListState::default(),
+            popup: None,
+            last_refresh: Instant::now(),
+        };
+        dashboard.refresh_leaderboard();
+        dashboard
+    }
+
+    fn push_log(&mut self, message: String) {
+        self.log.push_front(format!("[{}] {}", now(), message));
+        self.log.truncate(MAX_LOG);
+    }
+
+    fn refresh_leaderboard(&mut self) {
+        let allowlist = Allowlist::load();
+        let mut list: Vec<RipperData> = get_ripper().into_iter().filter(|a| a.count != 0 && !allowlist.contains(&a.name)).collect();
+        list.sort_by(|a, b| b.count.cmp(&a.count).then(a.name.cmp(&b.name)));
+        self.leaderboard = list;
+        self.last_refresh = Instant::now();
+    }
+
+    fn handle(&mut self, event: WatchEvent) {
+        match event {
+            WatchEvent::WaitingGame => self.push_log(tr!("watch.waiting_game")),
+            WatchEvent::LogOpened(path) => self.push_log(tr!("watch.log_path", path)),
+            WatchEvent::LogMissing => self.push_log(tr!("watch.log_missing_short")),
+            WatchEvent::GameClosed => {
+                self.roster.clear();
+                self.push_log(tr!("watch.game_closed"));
+            }
+            WatchEvent::RoomChanged => {
+                self.roster.clear();
+                self.push_log(tr!("watch.room_changed"));
+            }
+            WatchEvent::Joined(name) => {
+                if !self.roster.contains(&name) {
+                    self.roster.push(name.clone());
+                }
+                self.joins.push_front(format!("{} {}", now(), name));
+                self.joins.truncate(MAX_JOINS);
+            }
+            WatchEvent::Left(name) => self.roster.retain(|a| *a != name),
+            WatchEvent::Checking { name, message } => {
+                self.pending.insert(name, message);
+            }
+            WatchEvent::Waiting { name, elapsed, total } => {
+                self.pending.insert(name, tr!("watch.seconds_left", total.saturating_sub(elapsed)));
+            }
+            WatchEvent::Checked { name, avatars, count } => {
+                self.pending.remove(&name);
+                if let Some(count) = count {
+                    self.push_log(tr!("watch.leaked_on_join", name, avatars.join(", "), count));
+                    self.refresh_leaderboard();
+                }
+            }
+            WatchEvent::SuspectJoined { name, score } => self.push_log(tr!("watch.suspect_joined", score, name)),
+            WatchEvent::AlertFailed(error) => self.push_log(tr!("watch.alert_failed", error)),
+            WatchEvent::Rescanned { avatars } => {
+                if !avatars.is_empty() {
+                    self.push_log(tr!("watch.rescan_leaked", avatars.join(", ")));
+                } else {
+                    self.push_log(tr!("watch.rescan_clean"));
+                }
+            }
+        }
+    }
+
+    fn selected_name(&self) -> Option<String> {
+        match self.focus {
+            Focus::Roster => self.roster_state.selected().and_then(|i| self.roster.get(i).cloned()),
+            Focus::Leaderboard => self.board_state.selected().and_then(|i| self.leaderboard.get(i).map(|a| a.name.clone())),
+        }
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let (state, len) = match self.focus {
+            Focus::Roster => (&mut self.roster_state, self.roster.len()),
+            Focus::Leaderboard => (&mut self.board_state, self.leaderboard.len()),
+        };
+        if len == 0 {
+            state.select(None);
+            return;
+        }
+        let current = state.selected().unwrap_or(0) as isize;
+        state.select(Some((current + delta).clamp(0, len as isize - 1) as usize));
+    }
+
+    /// 키 입력 처리. 종료해야 하면 false
+    fn key(&mut self, code: KeyCode) -> bool {
+        if self.popup.is_some() {
+            if matches!(code, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q')) {
+                self.popup = None;
+            }
+            return true;
+        }
+
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Tab => {
+                self.focus = if self.focus == Focus::Roster { Focus::Leaderboard } else { Focus::Roster };
+            }
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::Enter => {
+                if let Some(name) = self.selected_name() {
+                    self.popup = Some((name.clone(), describe_user(&name)));
+                }
+            }
+            KeyCode::Char('m') => {
+                let muted = !self.control.muted.load(Ordering::Relaxed);
+                self.control.muted.store(muted, Ordering::Relaxed);
+                self.push_log(if muted { tr!("dashboard.muted") } else { tr!("dashboard.unmuted") });
+            }
+            KeyCode::Char('r') => {
+                self.push_log(tr!("dashboard.rescan_started"));
+                self.control.rescan();
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(45), Constraint::Min(5), Constraint::Length(1)])
+            .split(frame.size());
+        let top = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(34), Constraint::Percentage(33), Constraint::Percentage(33)])
+            .split(rows[0]);
+        let bottom = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .split(rows[1]);
+
+        let focused = Style::default().fg(Color::Cyan);
+        let highlight = Style::default().add_modifier(Modifier::REVERSED);
+
+        let roster: Vec<ListItem> = self.roster.iter().map(|a| ListItem::new(a.as_str())).collect();
+        let roster_block = Block::default().borders(Borders::ALL).title(tr!("dashboard.roster", self.roster.len()));
+        let roster_block = if self.focus == Focus::Roster { roster_block.border_style(focused) } else { roster_block };
+        frame.render_stateful_widget(List::new(roster).block(roster_block).highlight_style(highlight), top[0], &mut self.roster_state);
+
+        let joins: Vec<ListItem> = self.joins.iter().map(|a| ListItem::new(a.as_str())).collect();
+        frame.render_widget(List::new(joins).block(Block::default().borders(Borders::ALL).title(tr!("dashboard.recent_joins"))), top[1]);
+
+        let pending: Vec<ListItem> = self.pending.iter().map(|(name, status)| ListItem::new(format!("{} - {}", name, status))).collect();
+        frame.render_widget(List::new(pending).block(Block::default().borders(Borders::ALL).title(tr!("dashboard.pending"))), top[2]);
+
+        let board: Vec<ListItem> = self.leaderboard.iter().enumerate()
+            .map(|(i, a)| {
+                let row = tr!("dashboard.board_row", format!("{:>3}", i + 1), a.name, a.count);
+                ListItem::new(a.verdict.as_ref().map_or(row.clone(), |b| format!("{} [{}]", row, b.kind.label())))
+            })
+            .collect();
+        let board_block = Block::default().borders(Borders::ALL).title(tr!("dashboard.leaderboard"));
+        let board_block = if self.focus == Focus::Leaderboard { board_block.border_style(focused) } else { board_block };
+        frame.render_stateful_widget(List::new(board).block(board_block).highlight_style(highlight), bottom[0], &mut self.board_state);
+
+        let log: Vec<ListItem> = self.log.iter().map(|a| ListItem::new(a.as_str())).collect();
+        frame.render_widget(List::new(log).block(Block::default().borders(Borders::ALL).title(tr!("dashboard.event_log"))), bottom[1]);
+
+        let muted = if self.control.muted.load(Ordering::Relaxed) { tr!("dashboard.muted_tag") } else { String::new() };
+        let help = tr!("dashboard.help", muted);
+        frame.render_widget(Paragraph::new(help).style(Style::default().fg(Color::DarkGray)), rows[2]);
+
+        if let Some((name, lines)) = &self.popup {
+            let area = centered(frame.size(), 70, 60);
+            let text: Vec<Line> = lines.iter().map(|a| Line::from(a.as_str())).collect();
+            frame.render_widget(Clear, area);
+            frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }).block(Block::default().borders(Borders::ALL).title(name.as_str())), area);
+        }
+    }
+}
+
+fn centered(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage((100 - percent_y) / 2), Constraint::Percentage(percent_y), Constraint::Percentage((100 - percent_y) / 2)])
+        .split(area);
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage((100 - percent_x) / 2), Constraint::Percentage(percent_x), Constraint::Percentage((100 - percent_x) / 2)])
+        .split(vertical[1])[1]
+}
+
+fn event_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, dashboard: &mut Dashboard, receiver: &Receiver<WatchEvent>) -> io::Result<()> {
+    loop {
+        loop {
+            match receiver.try_recv() {
+                Ok(event) => dashboard.handle(event),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return Ok(()),
+            }
+        }
+        if dashboard.last_refresh.elapsed() > REFRESH_INTERVAL {
+            dashboard.refresh_leaderboard();
+        }
+
+        terminal.draw(|frame| dashboard.draw(frame))?;
+
+        if event::poll(Duration::from_millis(200))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !dashboard.key(key.code) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// 감시 화면 실행. q 를 누르면 종료
+pub fn run() -> io::Result<()> {
+    let (control, receiver) = watch::new_control();
+    watch::spawn(control.clone());
+    let mut dashboard = Dashboard::new(control);
+
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+
+    let result = event_loop(&mut terminal, &mut dashboard, &receiver);
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    result
+}
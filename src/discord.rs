@@ -0,0 +1,309 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+use reqwest::blocking::Client;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+use crate::alert::{Alert, AlertSink};
+use crate::config::config;
+use crate::library::format_time;
+
+const QUEUE_FILE: &str = "discord_queue.json";
+// 디스코드 임베드 필드 값 최대 길이
+const FIELD_LIMIT: usize = 1024;
+// 요청 제한에 걸렸을 때 한 메세지를 다시 보내는 최대 횟수
+const MAX_RATE_LIMIT_RETRY: u32 = 5;
+const EMBED_COLOR: u32 = 0xE74C3C;
+// 오래 끊겨 있어도 큐 파일이 끝없이 커지지 않도록 남겨두는 최대 메세지 수
+const MAX_QUEUE: usize = 100;
+
+// 여러 알림 스레드가 동시에 큐 파일을 건드리지 않도록
+static QUEUE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 디스코드 웹훅으로 임베드 전송. 보내지 못한 메세지는 큐 파일에 남겨 다음 알림이나 flush 때 다시 보낸다
+pub struct DiscordSink {
+    pub url: String,
+    pub queue: PathBuf,
+}
+
+enum SendError {
+    /// 네트워크 오류 등 나중에 다시 보내면 되는 오류
+    Retry(String),
+    /// 잘못된 메세지라 다시 보내도 소용 없는 오류
+    Drop(String),
+}
+
+fn truncate(value: String) -> String {
+    if value.chars().count() <= FIELD_LIMIT {
+        return value;
+    }
+    let mut value: String = value.chars().take(FIELD_LIMIT - 1).collect();
+    value.push('…');
+    value
+}
+
+fn field(name: &str, value: String) -> Value {
+    let value = if value.is_empty() { "-".to_string() } else { value };
+    json!({ "name": name, "value": truncate(value), "inline": false })
+}
+
+/// 알림을 디스코드 웹훅 메세지로 변환
+pub fn payload(alert: &Alert) -> Value {
+    let mut fields = vec![];
+    if let Some(leak) = &alert.leak {
+        fields.push(field(&tr!("discord.avatars"), leak.avatars.join("\n")));
+        fields.push(field(&tr!("discord.leak_time"), format_time(alert.time)));
+        let suspects: Vec<String> = leak.suspects.iter().map(|(name, score)| tr!("common.name_count_paren", name, score)).collect();
+        fields.push(field(&tr!("discord.suspects"), suspects.join("\n")));
+        fields.push(field(&tr!("discord.instance"), leak.location.clone()));
+    }
+    if let Some(suspect) = &alert.suspect {
+        fields.push(field(&tr!("discord.suspects"), tr!("common.name_count_paren", suspect.name, suspect.score)));
+        fields.push(field(&tr!("discord.recent_evidence"), suspect.evidence.join("\n")));
+        fields.push(field(&tr!("discord.instance"), suspect.location.clone()));
+    }
+    let timestamp = Utc.timestamp_millis_opt(alert.time).single().map(|a| a.to_rfc3339()).unwrap_or_default();
+
+    json!({
+        "username": "Anti-Ripper",
+        "embeds": [{
+            "title": alert.title,
+            "description": alert.message,
+            "color": EMBED_COLOR,
+            "timestamp": timestamp,
+            "fields": fields,
+        }]
+    })
+}
+
+/// 429 응답의 대기 시간. 본문의 retry_after(초)를 먼저 보고 없으면 Retry-After 헤더 사용
+fn retry_after(response: reqwest::blocking::Response) -> Duration {
+    let header = response.headers().get("retry-after").and_then(|a| a.to_str().ok()).and_then(|a| a.parse::<f64>().ok());
+    let body = response.json::<Value>().ok().and_then(|a| a["retry_after"].as_f64());
+    Duration::from_secs_f64(body.or(header).unwrap_or(1.0).max(0.1))
+}
+
+impl DiscordSink {
+    /// 데이터 폴더의 큐 파일을 사용
+    pub fn new(url: String) -> DiscordSink {
+        DiscordSink { url, queue: config().data_path(QUEUE_FILE) }
+    }
+
+    fn get_queue(&self) -> Vec<Value> {
+        fs::read_to_string(&self.queue).ok()
+            .and_then(|a| serde_json::from_str(&a).ok())
+            .unwrap_or_default()
+    }
+
+    fn set_queue(&self, queue: &[Value]) -> Result<(), String> {
+        let text = serde_json::to_string(queue).map_err(|e| tr!("discord.queue_write_error", e))?;
+        fs::write(&self.queue, text).map_err(|e| tr!("discord.queue_write_error", e))
+    }
+
+    /// 오래된 메세지부터 순서대로 보낸다. 보내지 못한 메세지는 queue 에 남는다
+    fn deliver(&self, queue: &mut Vec<Value>, errors: &mut Vec<String>) {
+        let client = Client::new();
+        while let Some(message) = queue.first() {
+            match self.post(&client, message) {
+                Ok(()) => {
+                    queue.remove(0);
+                }
+                Err(SendError::Drop(e)) => {
+                    queue.remove(0);
+                    errors.push(e);
+                }
+                Err(SendError::Retry(e)) => {
+                    errors.push(tr!("discord.queued", e, queue.len()));
+                    break;
+                }
+            }
+        }
+    }
+
+    fn post(&self, client: &Client, message: &Value) -> Result<(), SendError> {
+        for _ in 0..MAX_RATE_LIMIT_RETRY {
+            let response = client.post(&self.url).json(message).send().map_err(|e| SendError::Retry(tr!("discord.send_error", e)))?;
+            let status = response.status();
+            if status.is_success() {
+                return Ok(());
+            }
+            if status == StatusCode::TOO_MANY_REQUESTS {
+                thread::sleep(retry_after(response));
+                continue;
+            }
+            if status.is_server_error() {
+                return Err(SendError::Retry(tr!("discord.server_error", status)));
+            }
+            return Err(SendError::Drop(tr!("discord.rejected", status, response.text().unwrap_or_default())));
+        }
+        Err(SendError::Retry(tr!("discord.rate_limited")))
+    }
+}
+
+impl AlertSink for DiscordSink {
+    fn name(&self) -> &'static str {
+        "discord"
+    }
+
+    fn send(&self, alert: &Alert) -> Result<(), String> {
+        let _lock = QUEUE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut queue = self.get_queue();
+        queue.push(payload(alert));
+
+        let mut errors = vec![];
+        if queue.len() > MAX_QUEUE {
+            let dropped = queue.len() - MAX_QUEUE;
+            queue.drain(..dropped);
+            errors.push(tr!("discord.queue_full", dropped));
+        }
+        self.deliver(&mut queue, &mut errors);
+        if let Err(e) = self.set_queue(&queue) {
+            errors.push(e);
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors.join(", ")) }
+    }
+
+    fn flush(&self) -> Result<(), String> {
+        let _lock = QUEUE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut queue = self.get_queue();
+        if queue.is_empty() {
+            return Ok(());
+        }
+
+        let mut errors = vec![];
+        self.deliver(&mut queue, &mut errors);
+        self.set_queue(&queue)?;
+
+        if errors.is_empty() { Ok(()) } else { Err(errors.join(", ")) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::process;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::alert::Severity;
+
+    /// 정해둔 응답을 차례대로 돌려주는 웹훅 대신 서버. 받은 요청 본문을 기록한다
+    fn stand_in(responses: Vec<(u16, &'static str)>) -> (String, Arc<Mutex<Vec<Value>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/webhook", listener.local_addr().unwrap());
+        let received = Arc::new(Mutex::new(vec![]));
+        let record = received.clone();
+        thread::spawn(move || {
+            for (code, body) in responses {
+                let Ok((stream, _)) = listener.accept() else { return };
+                let mut reader = BufReader::new(stream);
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap_or(0);
+                        }
+                    }
+                }
+                let mut request = vec![0; length];
+                reader.read_exact(&mut request).unwrap();
+                record.lock().unwrap().push(serde_json::from_slice(&request).unwrap_or(Value::Null));
+                let mut stream = reader.into_inner();
+                let _ = write!(stream, "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", code, body.len(), body);
+            }
+        });
+        (url, received)
+    }
+
+    fn sink(url: String, name: &str) -> DiscordSink {
+        let queue = env::temp_dir().join(format!("anti-ripper-test-{}-{}.json", process::id(), name));
+        let _ = fs::remove_file(&queue);
+        DiscordSink { url, queue }
+    }
+
+    fn alert(message: &str) -> Alert {
+        Alert::new(Severity::NewLeak, "title", message)
+    }
+
+    #[test]
+    fn sends_embed() {
+        let (url, received) = stand_in(vec![(204, "")]);
+        let sink = sink(url, "sends");
+        assert!(sink.send(&alert("leaked")).is_ok());
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0]["embeds"][0]["description"], "leaked");
+        assert!(sink.get_queue().is_empty());
+    }
+
+    #[test]
+    fn retries_after_rate_limit() {
+        let (url, received) = stand_in(vec![(429, r#"{"retry_after": 0.1}"#), (200, "")]);
+        let sink = sink(url, "rate_limit");
+        assert!(sink.send(&alert("leaked")).is_ok());
+        assert_eq!(received.lock().unwrap().len(), 2);
+        assert!(sink.get_queue().is_empty());
+    }
+
+    #[test]
+    fn queues_on_server_error_and_flushes() {
+        let (url, received) = stand_in(vec![(500, ""), (200, ""), (200, "")]);
+        let sink = sink(url, "server_error");
+        assert!(sink.send(&alert("first")).is_err());
+        assert_eq!(sink.get_queue().len(), 1);
+
+        // 큐에 남은 메세지는 새 알림 없이도 flush 로 보낸다
+        assert!(sink.flush().is_ok());
+        assert!(sink.get_queue().is_empty());
+        assert!(sink.flush().is_ok());
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[1]["embeds"][0]["description"], "first");
+    }
+
+    #[test]
+    fn drops_rejected_message() {
+        let (url, received) = stand_in(vec![(400, r#"{"message": "Invalid Form Body"}"#), (200, "")]);
+        let sink = sink(url, "rejected");
+        assert!(sink.send(&alert("bad")).is_err());
+        assert!(sink.get_queue().is_empty());
+        assert!(sink.send(&alert("good")).is_ok());
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[1]["embeds"][0]["description"], "good");
+    }
+
+    #[test]
+    fn caps_queue_length() {
+        let (url, _) = stand_in(vec![(500, "")]);
+        let sink = sink(url, "cap");
+        let queued: Vec<Value> = (0..MAX_QUEUE).map(|a| json!({ "content": a })).collect();
+        sink.set_queue(&queued).unwrap();
+        assert!(sink.send(&alert("newest")).is_err());
+        let queue = sink.get_queue();
+        assert_eq!(queue.len(), MAX_QUEUE);
+        assert_eq!(queue[0]["content"], 1);
+        assert_eq!(queue[MAX_QUEUE - 1]["embeds"][0]["description"], "newest");
+    }
+
+    #[test]
+    fn returns_write_error() {
+        let (url, _) = stand_in(vec![(500, "")]);
+        let mut sink = sink(url, "write_error");
+        sink.queue = env::temp_dir().join("anti-ripper-missing-dir").join("queue.json");
+        let error = sink.send(&alert("leaked")).unwrap_err();
+        assert!(error.contains(&tr!("discord.queue_write_error", "")));
+    }
+}
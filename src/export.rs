@@ -0,0 +1,305 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use chrono::{Local, NaiveDate, TimeZone};
+use clap::ValueEnum;
+use ed25519_dalek::SigningKey;
+use serde::Serialize;
+
+use crate::allowlist::Allowlist;
+use crate::external::{signing_payload, SharedSuspect};
+use crate::library::{format_time, get_ripper, get_user, parse_vrcx_time, vrcx_time};
+use crate::signing::{public_key, sign};
+use crate::structs::LeakEvent;
+use crate::verdict::Verdict;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Format {
+    Csv,
+    Json,
+    #[value(alias = "markdown")]
+    Md,
+}
+
+impl Format {
+    /// 파일 확장자로 형식 추측
+    pub fn from_path(path: &Path) -> Option<Format> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "csv" => Some(Format::Csv),
+            "json" => Some(Format::Json),
+            "md" | "markdown" => Some(Format::Md),
+            _ => None,
+        }
+    }
+}
+
+/// 내보낼 유저를 고르는 조건
+#[derive(Debug, Default)]
+pub struct Filter {
+    pub min_score: u32,
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+}
+
+impl Filter {
+    fn has_range(&self) -> bool {
+        self.since.is_some() || self.until.is_some()
+    }
+
+    fn contains(&self, time: i64) -> bool {
+        let start = self.since.map(local_millis);
+        // until 날짜는 그 날 하루 전체를 포함
+        let end = self.until.and_then(|a| a.succ_opt()).map(local_millis);
+        start.is_none_or(|a| time >= a) && end.is_none_or(|a| time < a)
+    }
+}
+
+fn local_millis(date: NaiveDate) -> i64 {
+    let time = date.and_hms_opt(0, 0, 0).unwrap();
+    Local.from_local_datetime(&time).earliest().map(|a| a.timestamp_millis()).unwrap_or(0)
+}
+
+#[derive(Debug, Serialize)]
+pub struct Suspect {
+    pub name: String,
+    pub user_id: Option<String>,
+    pub score: u32,
+    pub verdict: Option<Verdict>,
+    pub events: Vec<Evidence>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Evidence {
+    pub avatar_name: String,
+    pub avatar_id: String,
+    pub leak_time: String,
+    pub joined_at: String,
+    pub delta_seconds: i64,
+    pub overlap_seconds: i64,
+    pub location: String,
+    pub world_name: String,
+}
+
+impl From<&LeakEvent> for Evidence {
+    fn from(event: &LeakEvent) -> Self {
+        Evidence {
+            avatar_name: event.avatar_name.clone(),
+            avatar_id: event.avatar_id.clone(),
+            leak_time: vrcx_time(event.leak_time),
+            joined_at: vrcx_time(event.joined_at),
+            delta_seconds: event.delta,
+            overlap_seconds: event.overlap,
+            location: event.location.clone(),
+            world_name: event.world_name.clone(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    generated_at: String,
+    min_score: u32,
+    since: Option<String>,
+    until: Option<String>,
+    suspects: &'a [Suspect],
+    /// 아래 세 값은 `--sign` 일 때만 쓴다. `import` 가 공유 목록으로 확인하는 값
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    public_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CsvRow<'a> {
+    name: &'a str,
+    user_id: &'a str,
+    score: u32,
+    verdict: &'a str,
+    verdict_note: &'a str,
+    avatar_name: &'a str,
+    avatar_id: &'a str,
+    leak_time: String,
+    joined_at: String,
+    delta_seconds: Option<i64>,
+    overlap_seconds: Option<i64>,
+    location: &'a str,
+    world_name: &'a str,
+}
+
+/// 조건에 맞는 유저와 감지 근거를 점수 순서로 모은다
+pub fn collect(filter: &Filter) -> Vec<Suspect> {
+    let users = get_user();
+    let allowlist = Allowlist::load();
+    let mut suspects: Vec<Suspect> = get_ripper().into_iter().filter(|a| !allowlist.contains(&a.name)).filter_map(|ripper| {
+        let events: Vec<&LeakEvent> = ripper.events.iter().filter(|a| filter.contains(a.leak_time)).collect();
+        // 기간을 지정하면 그 기간 안의 근거만 점수로 센다
+        let score = if filter.has_range() { events.len() as u32 } else { ripper.count };
+        if score == 0 || score < filter.min_score {
+            return None;
+        }
+        let user_id = users.iter().find(|a| a.display_name == ripper.name).map(|a| a.user_id.clone());
+        Some(Suspect { user_id, score, verdict: ripper.verdict.clone(), events: events.into_iter().map(Evidence::from).collect(), name: ripper.name })
+    }).collect();
+    suspects.sort_by(|a, b| b.score.cmp(&a.score).then(a.name.cmp(&b.name)));
+    suspects
+}
+
+/// 출처 이름과 키를 주면 서명해서 다른 사람이 `import` 로 가져올 수 있게 한다
+pub fn to_json(suspects: &[Suspect], filter: &Filter, signer: Option<(&str, &SigningKey)>) -> String {
+    let mut report = Report {
+        generated_at: Local::now().to_rfc3339(),
+        min_score: filter.min_score,
+        since: filter.since.map(|a| a.to_string()),
+        until: filter.until.map(|a| a.to_string()),
+        suspects,
+        source: None,
+        public_key: None,
+        signature: None,
+    };
+    if let Some((source, key)) = signer {
+        let shared: Vec<SharedSuspect> = suspects.iter().map(|a| SharedSuspect { user_id: a.user_id.clone(), name: a.name.clone(), score: a.score }).collect();
+        report.signature = Some(sign(key, &signing_payload(source, Some(&report.generated_at), &shared)));
+        report.public_key = Some(public_key(key));
+        report.source = Some(source.to_string());
+    }
+    serde_json::to_string_pretty(&report).expect(&tr!("json.serialize_error"))
+}
+
+/// 감지 근거 한 줄에 한 행. 근거가 없는 유저는 빈 칸으로 한 행
+pub fn to_csv(suspects: &[Suspect]) -> String {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    for suspect in suspects {
+        let user_id = suspect.user_id.as_deref().unwrap_or("");
+        let verdict = suspect.verdict.as_ref().map_or("", |a| a.kind.as_str());
+        let verdict_note = suspect.verdict.as_ref().map_or("", |a| a.note.as_str());
+        if suspect.events.is_empty() {
+            writer.serialize(CsvRow { name: &suspect.name, user_id, score: suspect.score, verdict, verdict_note, avatar_name: "", avatar_id: "", leak_time: String::new(), joined_at: String::new(), delta_seconds: None, overlap_seconds: None, location: "", world_name: "" }).expect(&tr!("export.csv_error"));
+        }
+        for event in &suspect.events {
+            writer.serialize(CsvRow {
+                name: &suspect.name,
+                user_id,
+                score: suspect.score,
+                verdict,
+                verdict_note,
+                avatar_name: &event.avatar_name,
+                avatar_id: &event.avatar_id,
+                leak_time: event.leak_time.clone(),
+                joined_at: event.joined_at.clone(),
+                delta_seconds: Some(event.delta_seconds),
+                overlap_seconds: Some(event.overlap_seconds),
+                location: &event.location,
+                world_name: &event.world_name,
+            }).expect(&tr!("export.csv_error"));
+        }
+    }
+    String::from_utf8(writer.into_inner().expect(&tr!("export.csv_error"))).expect(&tr!("export.csv_error"))
+}
+
+/// 월드 이름을 알면 위치 앞에 붙인다
+fn md_location(event: &Evidence) -> String {
+    if event.world_name.is_empty() {
+        event.location.clone()
+    } else {
+        format!("{} ({})", event.world_name, event.location)
+    }
+}
+
+fn escape_md(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+fn display_time(value: &str) -> String {
+    parse_vrcx_time(value).map(format_time).unwrap_or(value.to_string())
+}
+
+pub fn to_markdown(suspects: &[Suspect], filter: &Filter) -> String {
+    let mut text = String::new();
+    writeln!(text, "{}", tr!("export.md_title")).unwrap();
+    writeln!(text).unwrap();
+    writeln!(text, "{}", tr!("export.md_generated", Local::now().format("%Y-%m-%d %H:%M:%S"))).unwrap();
+    writeln!(text, "{}", tr!("export.md_min_score", filter.min_score)).unwrap();
+    if filter.has_range() {
+        let since = filter.since.map(|a| a.to_string()).unwrap_or_default();
+        let until = filter.until.map(|a| a.to_string()).unwrap_or_default();
+        writeln!(text, "{}", tr!("export.md_range", since, until)).unwrap();
+    }
+    writeln!(text).unwrap();
+
+    if suspects.is_empty() {
+        writeln!(text, "{}", tr!("export.md_empty")).unwrap();
+        return text;
+    }
+
+    writeln!(text, "{}", tr!("export.md_summary")).unwrap();
+    writeln!(text).unwrap();
+    writeln!(text, "{}", tr!("export.md_summary_header")).unwrap();
+    writeln!(text, "|---|---|---|---|").unwrap();
+    for (i, suspect) in suspects.iter().enumerate() {
+        writeln!(text, "| {} | {} | {} | {} |", i + 1, escape_md(&suspect.name), suspect.user_id.as_deref().unwrap_or("-"), suspect.score).unwrap();
+    }
+
+    writeln!(text).unwrap();
+    writeln!(text, "{}", tr!("export.md_evidence")).unwrap();
+    for suspect in suspects {
+        writeln!(text).unwrap();
+        writeln!(text, "{}", tr!("export.md_user", escape_md(&suspect.name), suspect.score)).unwrap();
+        writeln!(text).unwrap();
+        if let Some(verdict) = &suspect.verdict {
+            writeln!(text, "{}", tr!("export.md_verdict", verdict.kind.label(), format_time(verdict.date), escape_md(&verdict.note))).unwrap();
+            writeln!(text).unwrap();
+        }
+        if suspect.events.is_empty() {
+            writeln!(text, "{}", tr!("export.md_no_evidence")).unwrap();
+            continue;
+        }
+        writeln!(text, "{}", tr!("export.md_evidence_header")).unwrap();
+        writeln!(text, "|---|---|---|---|---|---|").unwrap();
+        for event in &suspect.events {
+            writeln!(text, "{}", tr!("export.md_evidence_row", escape_md(&event.avatar_name), display_time(&event.leak_time), display_time(&event.joined_at), format!("{:+}", event.delta_seconds), event.overlap_seconds, escape_md(&md_location(event)))).unwrap();
+        }
+    }
+    text
+}
+
+/// 서명은 JSON 에만 붙는다
+pub fn render(format: Format, filter: &Filter, signer: Option<(&str, &SigningKey)>) -> String {
+    let suspects = collect(filter);
+    match format {
+        Format::Csv => to_csv(&suspects),
+        Format::Json => to_json(&suspects, filter, signer),
+        Format::Md => to_markdown(&suspects, filter),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::external::{SharedList, SignatureStatus};
+
+    fn suspect(name: &str, user_id: Option<&str>, score: u32) -> Suspect {
+        Suspect { name: name.to_string(), user_id: user_id.map(str::to_string), score, verdict: None, events: vec![] }
+    }
+
+    #[test]
+    fn signed_json_imports_as_verified() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let suspects = vec![suspect("b", Some("usr_b"), 2), suspect("a", None, 5)];
+        let text = to_json(&suspects, &Filter::default(), Some(("creator", &key)));
+        let list: SharedList = serde_json::from_str(&text).unwrap();
+        assert_eq!(list.source_name(), "creator");
+        assert_eq!(list.verify(), Ok(SignatureStatus::Verified));
+
+        let tampered: SharedList = serde_json::from_str(&text.replace("\"score\": 2", "\"score\": 20")).unwrap();
+        assert_eq!(tampered.verify(), Err(tr!("signature.mismatch")));
+    }
+
+    #[test]
+    fn unsigned_json_has_no_signature() {
+        let text = to_json(&[suspect("a", Some("usr_a"), 1)], &Filter::default(), None);
+        let list: SharedList = serde_json::from_str(&text).unwrap();
+        assert_eq!(list.verify(), Ok(SignatureStatus::Unsigned));
+    }
+}
@@ -0,0 +1,198 @@
+use std::fs;
+use std::path::Path;
+
+use base64::{Engine as _, engine::general_purpose};
+use chrono::Local;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::config::config;
+
+/// 다른 제작자가 공유한 리퍼 의심 유저 목록. `export -f json` 결과도 그대로 읽을 수 있고, `--sign` 으로 내보냈으면 서명도 확인된다.
+#[derive(Debug, Deserialize)]
+pub struct SharedList {
+    /// 목록을 만든 사람 이름
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default, alias = "generated_at")]
+    pub created_at: Option<String>,
+    pub suspects: Vec<SharedSuspect>,
+    /// 서명한 Ed25519 공개키 (base64)
+    #[serde(default)]
+    pub public_key: Option<String>,
+    /// `signing_payload` 에 대한 Ed25519 서명 (base64)
+    #[serde(default)]
+    pub signature: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedSuspect {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    pub name: String,
+    pub score: u32,
+}
+
+/// 로컬에서 계산한 감지 횟수와 따로 저장하는 외부 점수
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalData {
+    pub user_id: String,
+    pub name: String,
+    pub sources: Vec<ExternalSource>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalSource {
+    pub source: String,
+    /// 서명에 사용된 공개키. 서명이 없으면 비어 있음
+    pub public_key: String,
+    pub score: u32,
+    pub file: String,
+    pub created_at: Option<String>,
+    pub imported_at: String,
+    pub verified: bool,
+}
+
+impl ExternalData {
+    pub fn score(&self) -> u32 {
+        self.sources.iter().map(|a| a.score).sum()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SignatureStatus {
+    Unsigned,
+    Verified,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportResult {
+    pub added: usize,
+    pub updated: usize,
+    /// usr_ ID 가 없어서 건너뛴 유저 수
+    pub skipped: usize,
+}
+
+/// 서명 대상. 키 순서가 고정되도록 serde_json 기본 Map(BTreeMap) 사용
+pub fn signing_payload(source: &str, created_at: Option<&str>, suspects: &[SharedSuspect]) -> Vec<u8> {
+    let mut suspects = suspects.to_vec();
+    suspects.sort_by(|a, b| a.user_id.cmp(&b.user_id).then(a.name.cmp(&b.name)));
+    serde_json::to_vec(&json!({
+        "source": source,
+        "created_at": created_at,
+        "suspects": suspects,
+    })).expect(&tr!("json.serialize_error"))
+}
+
+pub fn decode_key(value: &str) -> Result<VerifyingKey, String> {
+    let bytes = general_purpose::STANDARD.decode(value.trim()).map_err(|_| tr!("signature.key_format"))?;
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_| tr!("signature.key_length"))?;
+    VerifyingKey::from_bytes(&bytes).map_err(|_| tr!("signature.key_invalid"))
+}
+
+pub fn decode_signature(value: &str) -> Result<Signature, String> {
+    let bytes = general_purpose::STANDARD.decode(value.trim()).map_err(|_| tr!("signature.format"))?;
+    Signature::from_slice(&bytes).map_err(|_| tr!("signature.length"))
+}
+
+impl SharedList {
+    pub fn source_name(&self) -> String {
+        self.source.clone().unwrap_or("unknown".to_string())
+    }
+
+    /// 서명이 있으면 확인하고, 맞지 않으면 오류
+    pub fn verify(&self) -> Result<SignatureStatus, String> {
+        match (&self.public_key, &self.signature) {
+            (None, None) => Ok(SignatureStatus::Unsigned),
+            (Some(key), Some(signature)) => {
+                let key = decode_key(key)?;
+                let signature = decode_signature(signature)?;
+                let payload = signing_payload(&self.source_name(), self.created_at.as_deref(), &self.suspects);
+                key.verify(&payload, &signature).map_err(|_| tr!("signature.mismatch"))?;
+                Ok(SignatureStatus::Verified)
+            }
+            _ => Err(tr!("signature.incomplete")),
+        }
+    }
+}
+
+pub fn get_external() -> Vec<ExternalData> {
+    fs::read_to_string(config().data_path("external.json")).ok()
+        .and_then(|a| serde_json::from_str(&a).ok())
+        .unwrap_or_default()
+}
+
+pub fn set_external(json: Vec<ExternalData>) {
+    fs::write(config().data_path("external.json"), serde_json::to_string(&json).unwrap()).expect(&tr!("io.write_error"));
+}
+
+pub fn find_external(user_id: &str) -> Option<ExternalData> {
+    get_external().into_iter().find(|a| a.user_id == user_id)
+}
+
+pub fn read_list(path: &Path) -> Result<SharedList, String> {
+    let text = fs::read_to_string(path).map_err(|e| tr!("io.read_path_error", path.display(), e))?;
+    serde_json::from_str(&text).map_err(|e| tr!("external.invalid_format", e))
+}
+
+/// 공유 목록을 외부 점수로 합치기. 같은 출처에서 다시 가져오면 점수를 새 값으로 바꾼다.
+pub fn import(path: &Path, source: Option<String>, allow_unsigned: bool) -> Result<ImportResult, String> {
+    let mut list = read_list(path)?;
+    let status = list.verify()?;
+    if status == SignatureStatus::Unsigned && !allow_unsigned {
+        return Err(tr!("external.unsigned"));
+    }
+    // 서명된 목록은 서명에 포함된 출처 이름을 그대로 사용
+    if status == SignatureStatus::Unsigned {
+        if let Some(source) = source {
+            list.source = Some(source);
+        }
+    }
+
+    let source = list.source_name();
+    let public_key = list.public_key.clone().unwrap_or_default();
+    let file = path.file_name().map(|a| a.to_string_lossy().to_string()).unwrap_or_default();
+    let imported_at = Local::now().to_rfc3339();
+
+    let mut external = get_external();
+    let mut result = ImportResult::default();
+    for suspect in list.suspects {
+        let Some(user_id) = suspect.user_id.filter(|a| a.starts_with("usr_")) else {
+            result.skipped += 1;
+            continue;
+        };
+        let entry = ExternalSource {
+            source: source.clone(),
+            public_key: public_key.clone(),
+            score: suspect.score,
+            file: file.clone(),
+            created_at: list.created_at.clone(),
+            imported_at: imported_at.clone(),
+            verified: status == SignatureStatus::Verified,
+        };
+
+        match external.iter_mut().find(|a| a.user_id == user_id) {
+            Some(data) => {
+                data.name = suspect.name;
+                match data.sources.iter_mut().find(|a| a.source == entry.source && a.public_key == entry.public_key) {
+                    Some(existing) => {
+                        *existing = entry;
+                        result.updated += 1;
+                    }
+                    None => {
+                        data.sources.push(entry);
+                        result.added += 1;
+                    }
+                }
+            }
+            None => {
+                external.push(ExternalData { user_id, name: suspect.name, sources: vec![entry] });
+                result.added += 1;
+            }
+        }
+    }
+    set_external(external);
+
+    Ok(result)
+}
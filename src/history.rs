@@ -0,0 +1,242 @@
+use std::fs;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use log::{info, warn};
+use regex::Regex;
+use rusqlite::{Connection, params};
+
+use crate::bundle::log_line_time;
+use crate::config::config;
+use crate::library::vrcx_time;
+use crate::worn;
+
+pub const HISTORY_FILE: &str = "history.db";
+// VRCX 와 같은 입장이 로그에도 있으면 로그 쪽은 숨긴다. 로그 시간은 초 단위라 앞뒤로 조금 여유를 둔다
+const DUPLICATE_TOLERANCE: i64 = 2000;
+
+/// 보관해둔 로그 파일 하나에서 읽은 기록
+#[derive(Debug, Default)]
+pub struct Ingested {
+    pub joins: usize,
+    pub leaves: usize,
+    pub locations: usize,
+}
+
+/// 파일마다 (이름, 결과)
+pub type IngestResults = Vec<(String, Result<Ingested, String>)>;
+
+pub fn history_path() -> PathBuf {
+    config().data_path(HISTORY_FILE)
+}
+
+/// VRCX 기록이나 로그에서 가져온 기록 중 하나라도 있으면 감지 계산을 할 수 있다
+pub fn available() -> bool {
+    config().vrcx_database().exists() || history_path().exists()
+}
+
+fn create_tables(conn: &Connection) {
+    conn.execute_batch("
+        CREATE TABLE IF NOT EXISTS log_join_leave (
+            created_at TEXT NOT NULL,
+            type TEXT NOT NULL,
+            display_name TEXT NOT NULL,
+            location TEXT NOT NULL,
+            user_id TEXT NOT NULL,
+            match_start TEXT NOT NULL,
+            match_end TEXT NOT NULL,
+            source TEXT NOT NULL,
+            UNIQUE (created_at, type, display_name)
+        );
+        CREATE TABLE IF NOT EXISTS log_location (
+            created_at TEXT NOT NULL,
+            location TEXT NOT NULL,
+            world_id TEXT NOT NULL,
+            world_name TEXT NOT NULL,
+            time INTEGER NOT NULL,
+            match_start TEXT NOT NULL,
+            match_end TEXT NOT NULL,
+            source TEXT NOT NULL,
+            UNIQUE (created_at, location)
+        );
+        CREATE INDEX IF NOT EXISTS log_join_leave_created_at ON log_join_leave (created_at);
+        CREATE INDEX IF NOT EXISTS log_location_created_at ON log_location (created_at);
+    ").expect(&tr!("db.query_error"));
+}
+
+/// VRCX 와 로그에서 가져온 기록을 합쳐서 VRCX 테이블 이름(gamelog_join_leave, gamelog_location) 으로 보여주는 연결.
+/// VRCX 에 이미 있는 기록은 로그 쪽을 빼므로 같은 입장이 두번 세지지 않는다
+pub fn open() -> Connection {
+    let conn = Connection::open(history_path()).expect(&tr!("db.open_error"));
+    create_tables(&conn);
+
+    let vrcx = config().vrcx_database();
+    let sql = if vrcx.exists() {
+        conn.execute("ATTACH DATABASE ?1 AS vrcx", [vrcx.to_string_lossy().to_string()]).expect(&tr!("db.open_error"));
+        "
+        CREATE TEMP VIEW gamelog_join_leave AS
+            SELECT created_at, type, display_name, location, user_id FROM vrcx.gamelog_join_leave
+            UNION ALL
+            SELECT created_at, type, display_name, location, user_id FROM main.log_join_leave l
+            WHERE NOT EXISTS (SELECT 1 FROM vrcx.gamelog_join_leave v WHERE v.type = l.type AND v.display_name = l.display_name AND v.created_at BETWEEN l.match_start AND l.match_end);
+        CREATE TEMP VIEW gamelog_location AS
+            SELECT created_at, location, world_id, world_name, time FROM vrcx.gamelog_location
+            UNION ALL
+            SELECT created_at, location, world_id, world_name, time FROM main.log_location l
+            WHERE NOT EXISTS (SELECT 1 FROM vrcx.gamelog_location v WHERE v.location = l.location AND v.created_at BETWEEN l.match_start AND l.match_end);
+        "
+    } else {
+        "
+        CREATE TEMP VIEW gamelog_join_leave AS
+            SELECT created_at, type, display_name, location, user_id FROM main.log_join_leave;
+        CREATE TEMP VIEW gamelog_location AS
+            SELECT created_at, location, world_id, world_name, time FROM main.log_location;
+        "
+    };
+    conn.execute_batch(sql).expect(&tr!("db.query_error"));
+    conn
+}
+
+/// 로그 한 줄에서 읽은 입장/퇴장
+struct JoinLeave {
+    time: i64,
+    kind: &'static str,
+    display_name: String,
+    user_id: String,
+    location: String,
+}
+
+/// 로그 한 줄에서 읽은 인스턴스 이동
+struct LocationRow {
+    time: i64,
+    location: String,
+    world_name: String,
+    /// 머문 시간 (밀리초)
+    duration: i64,
+}
+
+/// 로그 파일 하나를 VRCX 와 같은 방식으로 읽는다. 방을 나가거나 파일이 끝나면 남아있던 유저도 퇴장한 것으로 본다
+fn parse_log(reader: impl BufRead) -> (Vec<JoinLeave>, Vec<LocationRow>) {
+    let owner_pattern = worn::owner_pattern();
+    let join_pattern = Regex::new(r"OnPlayerJoined\s+(.+?)(?:\s+\((usr_[^)]+)\))?\s*$").expect(&tr!("common.regex_error"));
+    let left_pattern = Regex::new(r"OnPlayerLeft\s+(.+?)(?:\s+\((usr_[^)]+)\))?\s*$").expect(&tr!("common.regex_error"));
+    let room_pattern = Regex::new(r"\[Behaviour\] Entering Room: (.+)$").expect(&tr!("common.regex_error"));
+    let location_pattern = Regex::new(r"\[Behaviour\] Joining (wrld_\S+)").expect(&tr!("common.regex_error"));
+
+    let mut owner = String::new();
+    let mut joins: Vec<JoinLeave> = vec![];
+    let mut locations: Vec<LocationRow> = vec![];
+    let mut present: Vec<(String, String)> = vec![];
+    let mut world_name = String::new();
+    let mut last_time = None;
+
+    // 방을 나갈 때 남아있던 유저 퇴장 처리와 머문 시간 기록
+    fn leave_room(time: i64, present: &mut Vec<(String, String)>, joins: &mut Vec<JoinLeave>, locations: &mut [LocationRow]) {
+        let location = locations.last().map(|a| a.location.clone()).unwrap_or_default();
+        for (display_name, user_id) in present.drain(..) {
+            joins.push(JoinLeave { time, kind: "OnPlayerLeft", display_name, user_id, location: location.clone() });
+        }
+        if let Some(last) = locations.last_mut() {
+            if last.duration == 0 {
+                last.duration = (time - last.time).max(0);
+            }
+        }
+    }
+
+    for line in reader.lines().map_while(Result::ok) {
+        let Some(time) = log_line_time(&line) else { continue };
+        last_time = Some(time);
+
+        if let Some(captures) = owner_pattern.captures(&line) {
+            owner = captures[1].to_string();
+        } else if let Some(captures) = room_pattern.captures(&line) {
+            world_name = captures[1].trim().to_string();
+        } else if let Some(captures) = location_pattern.captures(&line) {
+            leave_room(time, &mut present, &mut joins, &mut locations);
+            locations.push(LocationRow { time, location: captures[1].to_string(), world_name: std::mem::take(&mut world_name), duration: 0 });
+        } else if line.contains("[Behaviour] OnLeftRoom") {
+            leave_room(time, &mut present, &mut joins, &mut locations);
+        } else if let Some(captures) = join_pattern.captures(&line) {
+            let display_name = captures[1].trim().to_string();
+            if display_name == owner {
+                continue;
+            }
+            let user_id = captures.get(2).map(|a| a.as_str().to_string()).unwrap_or_default();
+            let location = locations.last().map(|a| a.location.clone()).unwrap_or_default();
+            present.retain(|(a, _)| *a != display_name);
+            present.push((display_name.clone(), user_id.clone()));
+            joins.push(JoinLeave { time, kind: "OnPlayerJoined", display_name, user_id, location });
+        } else if let Some(captures) = left_pattern.captures(&line) {
+            let display_name = captures[1].trim().to_string();
+            if display_name == owner || !present.iter().any(|(a, _)| *a == display_name) {
+                continue;
+            }
+            present.retain(|(a, _)| *a != display_name);
+            let user_id = captures.get(2).map(|a| a.as_str().to_string()).unwrap_or_default();
+            let location = locations.last().map(|a| a.location.clone()).unwrap_or_default();
+            joins.push(JoinLeave { time, kind: "OnPlayerLeft", display_name, user_id, location });
+        }
+    }
+    if let Some(time) = last_time {
+        leave_room(time, &mut present, &mut joins, &mut locations);
+    }
+    (joins, locations)
+}
+
+/// 로그 파일 하나를 기록에 추가. 같은 파일을 다시 읽어도 중복되지 않는다
+fn ingest_file(conn: &mut Connection, path: &Path) -> Result<Ingested, String> {
+    let file = File::open(path).map_err(|e| tr!("ingest.open_error", path.display(), e))?;
+    let (joins, locations) = parse_log(BufReader::new(file));
+    let source = path.file_name().map(|a| a.to_string_lossy().to_string()).unwrap_or_default();
+
+    let tx = conn.transaction().map_err(|e| tr!("ingest.db_error", e))?;
+    let mut result = Ingested::default();
+    {
+        let mut insert_join = tx.prepare("INSERT OR IGNORE INTO log_join_leave (created_at, type, display_name, location, user_id, match_start, match_end, source) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)").map_err(|e| tr!("ingest.db_error", e))?;
+        for row in &joins {
+            let inserted = insert_join.execute(params![vrcx_time(row.time), row.kind, row.display_name, row.location, row.user_id, vrcx_time(row.time - DUPLICATE_TOLERANCE), vrcx_time(row.time + DUPLICATE_TOLERANCE), source]).map_err(|e| tr!("ingest.db_error", e))?;
+            match (inserted, row.kind) {
+                (0, _) => {}
+                (_, "OnPlayerJoined") => result.joins += 1,
+                _ => result.leaves += 1,
+            }
+        }
+        let mut insert_location = tx.prepare("INSERT OR IGNORE INTO log_location (created_at, location, world_id, world_name, time, match_start, match_end, source) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)").map_err(|e| tr!("ingest.db_error", e))?;
+        for row in &locations {
+            let world_id = row.location.split(':').next().unwrap_or_default();
+            result.locations += insert_location.execute(params![vrcx_time(row.time), row.location, world_id, row.world_name, row.duration, vrcx_time(row.time - DUPLICATE_TOLERANCE), vrcx_time(row.time + DUPLICATE_TOLERANCE), source]).map_err(|e| tr!("ingest.db_error", e))?;
+        }
+    }
+    tx.commit().map_err(|e| tr!("ingest.db_error", e))?;
+
+    // 아바타를 입고 있던 시간도 같이 기록
+    if let Ok(file) = File::open(path) {
+        worn::add_intervals(worn::parse_log(BufReader::new(file)));
+    }
+    Ok(result)
+}
+
+/// 폴더 안의 output_log 파일을 모두 기록에 추가
+pub fn ingest_dir(dir: &Path) -> Result<IngestResults, String> {
+    let entries = fs::read_dir(dir).map_err(|e| tr!("ingest.dir_error", dir.display(), e))?;
+    let mut files: Vec<PathBuf> = entries.flatten()
+        .map(|a| a.path())
+        .filter(|a| a.is_file() && a.file_name().is_some_and(|b| b.to_string_lossy().starts_with("output_log")))
+        .collect();
+    files.sort();
+
+    let mut conn = Connection::open(history_path()).map_err(|e| tr!("ingest.db_error", e))?;
+    create_tables(&conn);
+    let mut results = vec![];
+    for path in files {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let result = ingest_file(&mut conn, &path);
+        match &result {
+            Ok(a) => info!("ingested log file={} joins={} leaves={} locations={}", name, a.joins, a.leaves, a.locations),
+            Err(e) => warn!("ingest failed file={} error={}", name, e),
+        }
+        results.push((name, result));
+    }
+    Ok(results)
+}
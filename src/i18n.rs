@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::env;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+// 실행 파일에 같이 넣는 메세지 목록. 키 목록이 서로 같은지는 아래 테스트에서 확인한다
+const KO: &str = include_str!("../locales/ko.toml");
+const EN: &str = include_str!("../locales/en.toml");
+
+// 언어를 고르는 환경 변수. 설정 파일을 읽기 전에도 사용한다
+pub const ENV_LANG: &str = "ANTI_RIPPER_LANG";
+
+static LOCALE: AtomicU8 = AtomicU8::new(Locale::Ko as u8);
+static CATALOGS: OnceLock<[HashMap<String, String>; 2]> = OnceLock::new();
+
+/// 화면에 출력하는 메세지 언어
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    Ko = 0,
+    En = 1,
+}
+
+impl Locale {
+    /// `ko`, `en_US.UTF-8` 같은 값에서 언어 찾기
+    pub fn parse(value: &str) -> Option<Locale> {
+        let value = value.trim().to_lowercase();
+        if value.starts_with("ko") {
+            Some(Locale::Ko)
+        } else if value.starts_with("en") {
+            Some(Locale::En)
+        } else {
+            None
+        }
+    }
+}
+
+/// 환경 변수로 언어 찾기. 없으면 한국어
+pub fn system_locale() -> Locale {
+    [ENV_LANG, "LC_ALL", "LC_MESSAGES", "LANG"].iter()
+        .filter_map(|a| env::var(a).ok())
+        .find_map(|a| Locale::parse(&a))
+        .unwrap_or(Locale::Ko)
+}
+
+pub fn set_locale(locale: Locale) {
+    LOCALE.store(locale as u8, Ordering::Relaxed);
+}
+
+pub fn locale() -> Locale {
+    if LOCALE.load(Ordering::Relaxed) == Locale::En as u8 { Locale::En } else { Locale::Ko }
+}
+
+/// `[section] key = "..."` 를 `section.key` 로 펼치기
+fn parse(text: &str) -> HashMap<String, String> {
+    let table: toml::Table = toml::from_str(text).expect("메세지 파일 구문 오류");
+    let mut map = HashMap::new();
+    for (section, value) in table {
+        let Some(values) = value.as_table() else { continue };
+        for (key, value) in values {
+            if let Some(value) = value.as_str() {
+                map.insert(format!("{}.{}", section, key), value.to_string());
+            }
+        }
+    }
+    map
+}
+
+fn catalogs() -> &'static [HashMap<String, String>; 2] {
+    CATALOGS.get_or_init(|| [parse(KO), parse(EN)])
+}
+
+/// 지금 언어의 메세지. 없으면 한국어, 그래도 없으면 키를 그대로 돌려준다
+pub fn text(key: &str) -> String {
+    let catalogs = catalogs();
+    catalogs[locale() as usize].get(key)
+        .or_else(|| catalogs[Locale::Ko as usize].get(key))
+        .cloned()
+        .unwrap_or_else(|| key.to_string())
+}
+
+/// 메세지의 `{0}`, `{1}` ... 을 값으로 바꾸기. 값 안의 중괄호는 다시 바꾸지 않는다
+pub fn format(key: &str, args: &[String]) -> String {
+    let template = text(key);
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template.as_str();
+
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let index = after.find('}').and_then(|end| after[..end].parse::<usize>().ok().map(|a| (a, end)));
+        match index.and_then(|(a, end)| args.get(a).map(|value| (value, end))) {
+            Some((value, end)) => {
+                result.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                result.push('{');
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// 메세지 키로 지금 언어의 문장 만들기. 두번째 값부터 차례대로 `{0}`, `{1}` ... 에 넣는다
+macro_rules! tr {
+    ($key:expr) => {
+        $crate::i18n::text($key)
+    };
+    ($key:expr, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::format($key, &[$($arg.to_string()),+])
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::fs;
+    use std::path::Path;
+
+    use super::*;
+
+    fn placeholders(text: &str) -> BTreeSet<String> {
+        text.split('{').skip(1).filter_map(|a| a.split_once('}')).map(|(a, _)| a.to_string()).collect()
+    }
+
+    #[test]
+    fn every_key_in_every_bundle() {
+        let [ko, en] = catalogs();
+        let mut problems = vec![];
+        for key in ko.keys().filter(|a| !en.contains_key(*a)) {
+            problems.push(format!("en.toml 에 {} 가 없습니다", key));
+        }
+        for key in en.keys().filter(|a| !ko.contains_key(*a)) {
+            problems.push(format!("ko.toml 에 {} 가 없습니다", key));
+        }
+        for (key, text) in ko {
+            if en.get(key).is_some_and(|a| placeholders(text) != placeholders(a)) {
+                problems.push(format!("{} 의 {{n}} 값이 서로 다릅니다", key));
+            }
+        }
+        problems.sort();
+        assert!(problems.is_empty(), "{}", problems.join("\n"));
+    }
+
+    #[test]
+    fn every_used_key_exists() {
+        let [ko, _] = catalogs();
+        let mut problems = vec![];
+        let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        for entry in fs::read_dir(src).unwrap().flatten() {
+            let text = fs::read_to_string(entry.path()).unwrap_or_default();
+            for (index, _) in text.match_indices("tr!(\"") {
+                // include_str!( 같은 다른 매크로는 건너뛰기
+                if text[..index].ends_with(|a: char| a.is_alphanumeric() || a == '_') {
+                    continue;
+                }
+                let Some((key, _)) = text[index + 5..].split_once('"') else { continue };
+                if !ko.contains_key(key) {
+                    problems.push(format!("{} 에서 쓰는 {} 가 메세지 파일에 없습니다", entry.path().display(), key));
+                }
+            }
+        }
+        assert!(problems.is_empty(), "{}", problems.join("\n"));
+    }
+
+}
@@ -1,54 +1,64 @@
 use std::fs;
-use std::fs::File;
-use std::io::Read;
 
-use chrono::{DateTime, Duration, Local, NaiveDateTime, TimeZone};
-use chrono::format::{DelayedFormat, StrftimeItems};
-use dirs::config_dir;
+use chrono::{DateTime, Local, TimeZone, Utc};
 
+use crate::config::config;
 use crate::structs::{RipperData, UserData};
 
-pub fn convert_time<'a>(value: u64) -> DelayedFormat<StrftimeItems<'a>> {
-    let time = NaiveDateTime::from_timestamp_millis(value as i64).unwrap() - Duration::hours(9);
-    let datetime = DateTime::<Local>::from_utc(time, Local.offset_from_utc_datetime(&time));
-    return datetime.format("%Y-%m-%d %H:%M:%S");
+// 리퍼 스토어 시간은 한국 시간이 UTC 로 기록되어 있으므로 9시간을 빼야 한다
+const RIPPER_TIME_OFFSET: i64 = 9 * 60 * 60 * 1000;
+
+/// 리퍼 스토어 시간(밀리초)을 UTC 밀리초로 변환
+pub fn ripper_time(value: u64) -> i64 {
+    value as i64 - RIPPER_TIME_OFFSET
+}
+
+/// UTC 밀리초를 VRCX 데이터베이스 created_at 형식으로 변환
+pub fn vrcx_time(value: i64) -> String {
+    Utc.timestamp_millis_opt(value).unwrap().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
+}
+
+/// VRCX 데이터베이스 created_at 값을 UTC 밀리초로 변환
+pub fn parse_vrcx_time(value: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(value).ok().map(|a| a.timestamp_millis())
+}
+
+/// UTC 밀리초를 화면에 보여줄 현지 시간으로 변환
+pub fn format_time(value: i64) -> String {
+    Local.timestamp_millis_opt(value).unwrap().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+pub fn now_millis() -> i64 {
+    Utc::now().timestamp_millis()
 }
 
 pub fn get_user() -> Vec<UserData> {
-    let data: Vec<UserData> = serde_json::from_str(&*fs::read_to_string(config_dir().unwrap().join("VRCX/Anti-ripper/user_id.json")).unwrap_or_else(|_| {
-        fs::write(config_dir().unwrap().join("VRCX/Anti-Ripper/user_id.json"), "[]").expect("파일 쓰기 오류");
-        return fs::read_to_string(config_dir().unwrap().join("VRCX/Anti-ripper/user_id.json")).expect("파일 읽기 오류");
+    let data: Vec<UserData> = serde_json::from_str(&*fs::read_to_string(config().data_path("user_id.json")).unwrap_or_else(|_| {
+        fs::write(config().data_path("user_id.json"), "[]").expect(&tr!("io.write_error"));
+        return fs::read_to_string(config().data_path("user_id.json")).expect(&tr!("io.read_error"));
     })).unwrap_or_else(|_| {
-        serde_json::from_str(&*fs::read_to_string(config_dir().unwrap().join("VRCX/Anti-ripper/user_id.json")).unwrap()).unwrap()
+        serde_json::from_str(&*fs::read_to_string(config().data_path("user_id.json")).unwrap()).unwrap()
     });
     return data;
 }
 
 pub fn get_ripper() -> Vec<RipperData> {
-    let data: Vec<RipperData> = serde_json::from_str(&*fs::read_to_string(config_dir().unwrap().join("VRCX/Anti-ripper/ripper.json")).unwrap_or_else(|_| {
-        fs::write(config_dir().unwrap().join("VRCX/Anti-Ripper/ripper.json"), "[]").expect("파일 쓰기 오류");
-        return fs::read_to_string(config_dir().unwrap().join("VRCX/Anti-ripper/ripper.json")).expect("파일 읽기 오류");
+    let data: Vec<RipperData> = serde_json::from_str(&*fs::read_to_string(config().data_path("ripper.json")).unwrap_or_else(|_| {
+        fs::write(config().data_path("ripper.json"), "[]").expect(&tr!("io.write_error"));
+        return fs::read_to_string(config().data_path("ripper.json")).expect(&tr!("io.read_error"));
     })).unwrap_or_else(|_| {
-        fs::write(config_dir().unwrap().join("VRCX/Anti-Ripper/ripper.json"), "[]").expect("파일 쓰기 오류");
-        serde_json::from_str(&*fs::read_to_string(config_dir().unwrap().join("VRCX/Anti-ripper/ripper.json")).unwrap()).unwrap()
+        fs::write(config().data_path("ripper.json"), "[]").expect(&tr!("io.write_error"));
+        serde_json::from_str(&*fs::read_to_string(config().data_path("ripper.json")).unwrap()).unwrap()
     });
     return data;
 }
 
-pub fn get_id() -> String {
-    let user_id_file_path = config_dir().unwrap().join("VRCX/Anti-Ripper/user_id.txt");
-    let mut file = File::open(user_id_file_path).expect("파일 읽기 오류");
-    let mut user_id = String::new();
-    file.read_to_string(&mut user_id).expect("파일 읽기 오류");
-    return user_id;
-}
-
 pub fn set_user(json: Vec<UserData>) {
-    let ids = config_dir().unwrap().join("VRCX/Anti-Ripper/user_id.json");
+    let ids = config().data_path("user_id.json");
     fs::write(ids, serde_json::to_string(&json).unwrap()).unwrap();
 }
 
 pub fn set_ripper(json: Vec<RipperData>) {
-    let ids = config_dir().unwrap().join("VRCX/Anti-Ripper/ripper.json");
+    let ids = config().data_path("ripper.json");
     fs::write(ids, serde_json::to_string(&json).unwrap()).unwrap();
 }
\ No newline at end of file
@@ -0,0 +1,130 @@
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::Local;
+use log::{error, LevelFilter, Log, Metadata, Record};
+use serde_json::json;
+
+use crate::config::config;
+
+const LOG_FILE: &str = "anti-ripper.log";
+// 다른 라이브러리의 로그는 기록하지 않는다
+const TARGET: &str = env!("CARGO_CRATE_NAME");
+
+struct LogFile {
+    file: File,
+    size: u64,
+}
+
+/// 데이터 폴더에 한 줄씩 JSON 으로 기록하고, 크기가 넘으면 이전 파일을 뒤로 밀어낸다
+struct FileLogger {
+    level: LevelFilter,
+    path: PathBuf,
+    max_size: u64,
+    max_files: u32,
+    file: Mutex<Option<LogFile>>,
+}
+
+fn open(path: &Path) -> Option<LogFile> {
+    let file = OpenOptions::new().create(true).append(true).open(path).ok()?;
+    let size = file.metadata().map(|a| a.len()).unwrap_or(0);
+    Some(LogFile { file, size })
+}
+
+/// `anti-ripper.log` 다음 파일은 `anti-ripper.log.1`, `anti-ripper.log.2` ...
+fn rotated_path(path: &Path, index: u32) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+impl FileLogger {
+    fn rotate(&self, current: &mut Option<LogFile>) {
+        *current = None;
+        let _ = fs::remove_file(rotated_path(&self.path, self.max_files));
+        for index in (1..self.max_files).rev() {
+            let _ = fs::rename(rotated_path(&self.path, index), rotated_path(&self.path, index + 1));
+        }
+        if self.max_files > 0 {
+            let _ = fs::rename(&self.path, rotated_path(&self.path, 1));
+        } else {
+            let _ = fs::remove_file(&self.path);
+        }
+        *current = open(&self.path);
+    }
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level && metadata.target().starts_with(TARGET)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = json!({
+            "time": Local::now().to_rfc3339(),
+            "level": record.level().as_str(),
+            "target": record.target(),
+            "message": record.args().to_string(),
+        }).to_string() + "\n";
+
+        let mut current = self.file.lock().unwrap();
+        if current.as_ref().is_some_and(|a| a.size + line.len() as u64 > self.max_size) {
+            self.rotate(&mut current);
+        }
+        if let Some(log) = current.as_mut() {
+            if log.file.write_all(line.as_bytes()).is_ok() {
+                log.size += line.len() as u64;
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(log) = self.file.lock().unwrap().as_mut() {
+            let _ = log.file.flush();
+        }
+    }
+}
+
+pub fn log_path() -> PathBuf {
+    config().data_path(LOG_FILE)
+}
+
+/// 지금 기록 파일과 밀려난 이전 기록 파일들
+pub fn log_files() -> Vec<PathBuf> {
+    let path = log_path();
+    let mut files = vec![path.clone()];
+    files.extend((1..=config().logging.max_files).map(|a| rotated_path(&path, a)));
+    files
+}
+
+/// 설정을 불러온 뒤 한번만 호출. 프로그램이 멈추는 오류도 기록한다
+pub fn init() {
+    let settings = &config().logging;
+    let level = settings.level.parse().unwrap_or(LevelFilter::Info);
+    let path = log_path();
+    let logger = FileLogger {
+        level,
+        file: Mutex::new(open(&path)),
+        path,
+        max_size: settings.max_size_kb * 1024,
+        max_files: settings.max_files,
+    };
+    if log::set_logger(Box::leak(Box::new(logger))).is_err() {
+        return;
+    }
+    log::set_max_level(level);
+
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        error!("panic: {}", info);
+        log::logger().flush();
+        default_hook(info);
+    }));
+}
@@ -1,46 +1,75 @@
-use std::{env, fs, io, ptr, thread};
-use std::cell::Cell;
+use std::{fs, thread};
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::env::current_exe;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::process::{Command, exit};
-use std::rc::Rc;
-use std::sync::{Arc, Mutex};
 use std::thread::available_parallelism;
-use std::time::{Duration, SystemTime};
+use std::time::Duration;
 
 use base64::{Engine as _, engine::general_purpose};
-use chrono::format::{DelayedFormat, StrftimeItems};
-use dirs::{config_dir, home_dir};
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use chrono::{Local, NaiveDate, TimeZone, Utc};
+use indicatif::{ProgressBar, ProgressStyle};
+use log::{debug, info, warn};
+use rayon::prelude::*;
 use rayon::ThreadPoolBuilder;
-use regex::Regex;
 use reqwest::blocking::{Client, RequestBuilder};
 use reqwest::cookie::Cookie;
 use reqwest::header::{AUTHORIZATION, COOKIE, HeaderMap, HeaderValue, USER_AGENT};
-use rodio::{Decoder, OutputStream, Source};
 use rpassword::read_password;
-use rusqlite::Connection;
-use self_update::cargo_crate_version;
 use serde_json::{json, Value};
 use shadow_rs::shadow;
 use text_io::read;
 use ua_generator::ua::spoof_ua;
-use winapi::shared::minwindef::{DWORD, MAX_PATH};
-use winapi::um::tlhelp32::{CreateToolhelp32Snapshot, Process32First, Process32Next, PROCESSENTRY32};
-
-use crate::library::{convert_time, get_id, get_ripper, get_user, set_ripper, set_user};
-use crate::structs::{AvatarData, AvatarItem, AvatarList, RipperData, SaveData, SearchData, UserData};
 
+use crate::cli::{Commands, EXIT_FAILURE, EXIT_NOT_READY, EXIT_OK, EXIT_USAGE};
+use crate::config::{config, MANIFEST_FILE};
+use crate::correlate::credit_leak;
+use crate::export::{Filter, Format};
+use crate::external::{find_external, get_external};
+use crate::library::{format_time, get_ripper, get_user, ripper_time, set_user};
+use crate::allowlist::Allowlist;
+use crate::significance::Ranking;
+use crate::signing::signing_key;
+use crate::verdict::VerdictKind;
+use crate::structs::{AvatarItem, AvatarList, AvatarRecord, Leaked, SearchData, UserData};
+
+#[macro_use]
+mod i18n;
 mod structs;
+mod alert;
+mod discord;
 mod library;
-
-const LOGIN_URL: &str = "https://api.vrchat.cloud/api/1/auth/user";
-const TOTP_URL: &str = "https://api.vrchat.cloud/api/1/auth/twofactorauth/totp/verify";
-const EMAIL_URL: &str = "https://api.vrchat.cloud/api/1/auth/twofactorauth/emailotp/verify";
-const API_URL: &str = "https://api.ripper.store/api/v2/avatars/search";
-const API_DETAIL_URL: &str = "https://api.ripper.store/api/v2/avatars/detail";
+mod config;
+mod cli;
+mod watch;
+mod dashboard;
+mod correlate;
+mod export;
+mod external;
+mod signing;
+mod bundle;
+mod update;
+mod serve;
+mod logging;
+mod audit;
+mod profile;
+mod avatars;
+mod worn;
+mod presence;
+mod worlds;
+mod significance;
+mod allowlist;
+mod verdict;
+mod history;
+
+const LOGIN_PATH: &str = "auth/user";
+const TOTP_PATH: &str = "auth/twofactorauth/totp/verify";
+const EMAIL_PATH: &str = "auth/twofactorauth/emailotp/verify";
+const SEARCH_PATH: &str = "avatars/search";
+const DETAIL_PATH: &str = "avatars/detail";
 const PROGRAM_USER_AGENT: &str = "Ripper Store User Detector / 1.0.10 cloud9350@naver.com";
 
 shadow!(build);
@@ -52,10 +81,10 @@ fn login() {
 
     loop {
         // 로그인
-        println!("브챗 아이디를 입력하세요");
+        println!("{}", tr!("login.enter_id"));
         let id: String = read!();
-        println!("브챗 비밀번호를 입력하세요");
-        let pw: String = read_password().expect("비밀번호 입력 오류");
+        println!("{}", tr!("login.enter_password"));
+        let pw: String = read_password().expect(&tr!("login.password_input_error"));
 
         // 로그인 Header 생성
         let account_auth_header = HeaderValue::from_str(&format!("Basic {}", general_purpose::STANDARD_NO_PAD.encode(&format!("{}:{}", id, pw)))).unwrap();
@@ -66,13 +95,13 @@ fn login() {
         let mut login_header = HeaderMap::new();
         login_header.insert(USER_AGENT, PROGRAM_USER_AGENT.parse().unwrap());
         login_header.insert(AUTHORIZATION, account_auth_header);
-        let login_get_response = client.get(LOGIN_URL).headers(login_header.clone()).send().expect("브챗 로그인 오류");
-        let cloned = client.get(LOGIN_URL).headers(login_header).send().expect("브챗 로그인 오류");
+        let login_get_response = client.get(config().vrchat_url(LOGIN_PATH)).headers(login_header.clone()).send().expect(&tr!("login.request_error"));
+        let cloned = client.get(config().vrchat_url(LOGIN_PATH)).headers(login_header).send().expect(&tr!("login.request_error"));
 
         if login_get_response.status().is_success() {
-            let otp_type = cloned.text().expect("데이터 변환 오류").contains("totp");
+            let otp_type = cloned.text().expect(&tr!("common.convert_error")).contains("totp");
 
-            println!("2단계 인증 코드 6자리를 입력하세요. 인증 앱 또는 이메일을 확인하시면 됩니다.");
+            println!("{}", tr!("login.enter_otp"));
             loop {
                 let token_cookie = login_get_response.cookies();
                 let code: String = read!();
@@ -81,19 +110,19 @@ fn login() {
 
                 let mut post_headers = HeaderMap::new();
                 post_headers.insert(USER_AGENT, PROGRAM_USER_AGENT.parse().unwrap());
-                post_headers.insert(COOKIE, HeaderValue::from_str(&filter_cookie(token_cookie)).expect("쿠키 값 가져오기 실패"));
+                post_headers.insert(COOKIE, HeaderValue::from_str(&filter_cookie(token_cookie)).expect(&tr!("login.cookie_error")));
 
                 // 2단계 인증이 인증 앱인지 이메일 인증인지 확인
                 let mut post_request: RequestBuilder;
                 if otp_type {
-                    post_request = client.post(TOTP_URL).headers(post_headers);
+                    post_request = client.post(config().vrchat_url(TOTP_PATH)).headers(post_headers);
                 } else {
-                    post_request = client.post(EMAIL_URL).headers(post_headers);
+                    post_request = client.post(config().vrchat_url(EMAIL_PATH)).headers(post_headers);
                 };
 
                 post_request = post_request.json(&map);
 
-                let post_response = post_request.send().expect("브챗 2단계 로그인 오류");
+                let post_response = post_request.send().expect(&tr!("login.otp_request_error"));
 
                 if post_response.status().is_success() {
                     let token_cookie = post_response.cookies();
@@ -102,49 +131,53 @@ fn login() {
                     let mut token_login_headers = HeaderMap::new();
                     token_login_headers.insert(USER_AGENT, PROGRAM_USER_AGENT.parse().unwrap());
                     token_login_headers.insert(AUTHORIZATION, account_auth_header);
-                    token_login_headers.insert(COOKIE, HeaderValue::from_str(&filter_cookie(token_cookie)).expect("쿠키 값 가져오기 실패"));
+                    token_login_headers.insert(COOKIE, HeaderValue::from_str(&filter_cookie(token_cookie)).expect(&tr!("login.cookie_error")));
 
-                    let token_login = client.get(LOGIN_URL).headers(token_login_headers).send().expect("브챗 인증 토큰 로그인 오류");
+                    let token_login = client.get(config().vrchat_url(LOGIN_PATH)).headers(token_login_headers).send().expect(&tr!("login.token_error"));
 
                     if token_login.status().is_success() {
-                        let data = config_dir().unwrap().join("VRCX/Anti-Ripper/auth");
-                        fs::write(data, &filter_cookie(token_login.cookies())).expect("파일 쓰기 오류");
-                        println!("로그인 성공");
+                        let data = profile::active_path("auth");
+                        if let Some(parent) = data.parent() {
+                            fs::create_dir_all(parent).expect(&tr!("io.create_dir_error"));
+                        }
+                        fs::write(data, &filter_cookie(token_login.cookies())).expect(&tr!("io.write_error"));
+                        println!("{}", tr!("login.success", profile::active()));
                         break;
                     }
                 } else {
-                    println!("2단계 인증 코드가 맞지 않습니다!");
+                    println!("{}", tr!("login.otp_wrong"));
                 }
             }
             break;
         } else {
-            println!("아이디 또는 비밀번호가 틀렸습니다. 다시 입력 해 주세요.");
+            println!("{}", tr!("login.wrong_credentials"));
         }
     }
 }
 
 fn get_info_from_server_bulk(url: String, count: u64, pb: &ProgressBar) -> Value {
-    let token = fs::read_to_string(config_dir().unwrap().join("VRCX/Anti-ripper/auth")).expect("인증 토큰 파일 읽기 오류");
-    let url = format!("https://api.vrchat.cloud/api/1/users?search={}&n={}", url, count);
+    let token = fs::read_to_string(profile::active_path("auth")).expect(&tr!("login.token_read_error"));
+    let url = format!("{}?search={}&n={}", config().vrchat_url("users"), url, count);
     let client = Client::new();
     let mut headers = HeaderMap::new();
     headers.insert(USER_AGENT, PROGRAM_USER_AGENT.parse().unwrap());
-    headers.insert(COOKIE, HeaderValue::from_str(&*token).expect("인증 토큰 파일 변환 오류"));
+    headers.insert(COOKIE, HeaderValue::from_str(&*token).expect(&tr!("login.token_convert_error")));
 
-    let mut response = client.get(url.clone()).headers(headers.clone()).send().expect("브챗 데이터 다운로드 오류");
+    let mut response = client.get(url.clone()).headers(headers.clone()).send().expect(&tr!("vrchat.download_error"));
     while !response.status().is_success() {
-        pb.set_message("브챗 서버가 과열 되었습니다! 식을 때 까지 대기중...");
-        for _ in 0..304 {
+        warn!("vrchat user search failed status={}", response.status());
+        pb.set_message(tr!("vrchat.rate_limited"));
+        for _ in 0..config().timing.rate_limit_cooldown - 1 {
             thread::sleep(Duration::from_secs(1));
             pb.tick();
         }
-        response = client.get(url.clone()).headers(headers.clone()).send().expect("브챗 데이터 다운로드 오류");
+        response = client.get(url.clone()).headers(headers.clone()).send().expect(&tr!("vrchat.download_error"));
         pb.set_message("");
     }
 
     return if response.status().is_success() {
-        let body = response.text().expect("브챗 데이터 읽기 오류");
-        let json: Value = serde_json::from_str(&*body).expect("브챗 데이터 해석 오류");
+        let body = response.text().expect(&tr!("vrchat.read_error"));
+        let json: Value = serde_json::from_str(&*body).expect(&tr!("vrchat.parse_error"));
         json
     } else {
         json!({})
@@ -152,207 +185,172 @@ fn get_info_from_server_bulk(url: String, count: u64, pb: &ProgressBar) -> Value
 }
 
 fn get_info_from_server(user_name: String, pb: &ProgressBar) -> Value {
-    let token = fs::read_to_string(config_dir().unwrap().join("VRCX/Anti-ripper/auth")).expect("인증 토큰 파일 읽기 오류");
-    let url = format!("https://api.vrchat.cloud/api/1/users?search={}&n=1", user_name);
+    let token = fs::read_to_string(profile::active_path("auth")).expect(&tr!("login.token_read_error"));
+    let url = format!("{}?search={}&n=1", config().vrchat_url("users"), user_name);
     let client = Client::new();
     let mut headers = HeaderMap::new();
     headers.insert(USER_AGENT, PROGRAM_USER_AGENT.parse().unwrap());
-    headers.insert(COOKIE, HeaderValue::from_str(&*token).expect("인증 토큰 파일 변환 오류"));
+    headers.insert(COOKIE, HeaderValue::from_str(&*token).expect(&tr!("login.token_convert_error")));
 
-    let mut response = client.get(url.clone()).headers(headers.clone()).send().expect("브챗 데이터 다운로드 오류");
+    let mut response = client.get(url.clone()).headers(headers.clone()).send().expect(&tr!("vrchat.download_error"));
     while !response.status().is_success() {
-        pb.set_message("브챗 서버가 과열 되었습니다! 식을 때 까지 대기중...");
-        thread::sleep(Duration::from_secs(305));
-        response = client.get(url.clone()).headers(headers.clone()).send().expect("브챗 데이터 다운로드 오류");
+        warn!("vrchat user search failed status={}", response.status());
+        pb.set_message(tr!("vrchat.rate_limited"));
+        thread::sleep(Duration::from_secs(config().timing.rate_limit_cooldown));
+        response = client.get(url.clone()).headers(headers.clone()).send().expect(&tr!("vrchat.download_error"));
         pb.set_message("");
     }
 
     return if response.status().is_success() {
-        let body = response.text().expect("브챗 데이터 읽기 오류");
-        let json: Value = serde_json::from_str(&*body).expect("브챗 데이터 해석 오류");
+        let body = response.text().expect(&tr!("vrchat.read_error"));
+        let json: Value = serde_json::from_str(&*body).expect(&tr!("vrchat.parse_error"));
         json
     } else {
         json!({})
     };
 }
 
-fn get_info_from_ripper(user_id: &str) -> Result<(), Box<dyn std::error::Error>> {
-    fn put(base_time: DelayedFormat<StrftimeItems>, range_time: DelayedFormat<StrftimeItems>) {
-        let database_path = config_dir().unwrap().join("VRCX/VRCX.sqlite3");
-        let conn = Connection::open(database_path).expect("VRCX 데이터베이스 오류");
-        let sql = format!("SELECT created_at,display_name,user_id FROM gamelog_join_leave WHERE type='OnPlayerJoined' BETWEEN '{}' AND '{}'", base_time, range_time);
-        let mut stmt = conn.prepare(&sql).expect("데이터베이스 쿼리 오류");
-
-        let total_user = Rc::new(Cell::new(0));
-        let result = stmt.query_map([], |row| {
-            let total_user = Rc::clone(&total_user);
-            total_user.set(total_user.get() + 1);
-            Ok(UserData {
-                created_at: row.get(0).expect("데이터베이스에서 created_at 값 읽기 오류"),
-                display_name: row.get(1).expect("데이터베이스 display_name 값 읽기 오류"),
-                user_id: row.get(2).expect("데이터베이스 user_id 값 읽기 오류"),
-            })
-        }).expect("데이터베이스 쿼리 실행 오류");
-
-        get_ripper();
-
-        for value in result {
-            let data = value;
-            let name = data.unwrap().display_name;
-
-            let mut ripper_json = get_ripper();
-            for mut r in ripper_json.clone() {
-                if r.name == name {
-                    r.count += 1;
-                }
-            }
-
-            if ripper_json.iter().find(|a| a.name == name).is_none() {
-                ripper_json.push(RipperData { name, count: 0 })
-            }
+/// 로그인 한 모든 계정의 아바타를 리퍼 스토어에서 검색하여 감지 횟수 계산. 결과는 하나의 ripper.json 에 합친다
+fn get_info_from_ripper() -> Result<(), Box<dyn std::error::Error>> {
+    // 감지 전에 남아있는 로그에서 아바타를 입고 있던 시간을 먼저 읽는다
+    worn::scan_logs();
+    if config().allowlist.sync_friends {
+        sync_friends();
+    }
+    for (name, user_id) in profile::owned() {
+        println!("{}", tr!("ripper.checking_account", name, user_id));
+        credit_author(&user_id);
+    }
 
-            set_ripper(ripper_json);
-        }
+    if get_ripper().is_empty() {
+        println!("{}", tr!("ripper.no_data"));
+        println!("{}", tr!("ripper.no_data_hint"));
     }
 
+    let checked = config().data_path("store_check.txt");
+    fs::write(checked, "VRCX 데이터를 사용하여 리퍼 스토어에서 뜯긴 아바타를 모두 계산 했다는 확인 파일").expect(&tr!("ripper.checked_marker_error"));
+
+    Ok(())
+}
+
+/// 한 제작자 ID 로 올린 아바타가 뜯긴 시간마다 그때 있던 유저들에게 감지 횟수 부여
+fn credit_author(user_id: &str) {
     let client = Client::new();
     let ua = spoof_ua();
 
     let params = format!("?category=authorId&page={}&search={}&status=both&ordering=none&platform=all&limit=36", "1", user_id);
-    let response = client.get(format!("{}{}", API_URL, params))
+    let response = client.get(format!("{}{}", config().ripper_url(SEARCH_PATH), params))
         .header(USER_AGENT, ua)
         .send()
-        .expect("리퍼 스토어 데이터 요청 오류");
+        .expect(&tr!("ripper.request_error"));
     if response.status().is_success() {
-        let body = response.text().expect("리퍼 스토어 데이터 읽기 오류");
-        let json: Value = serde_json::from_str(&*body).expect("리퍼 스토어 데이터 해석 오류");
-        let page: u64 = json["pages"].as_u64().expect("리퍼 스토어 데이터 형식 변환 오류");
+        let body = response.text().expect(&tr!("ripper.read_error"));
+        let json: Value = serde_json::from_str(&*body).expect(&tr!("ripper.parse_error"));
+        let page: u64 = json["pages"].as_u64().expect(&tr!("ripper.convert_error"));
 
-        let avatar_total = json["count"].as_u64().expect("리퍼 스토어 데이터 형식 변환 오류");
-        let sty = ProgressStyle::with_template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}").expect("진행바 구문 오류").progress_chars("##-");
+        let avatar_total = json["count"].as_u64().expect(&tr!("ripper.convert_error"));
+        let sty = ProgressStyle::with_template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}").expect(&tr!("common.progress_style_error")).progress_chars("##-");
         let avatar_progress = ProgressBar::new(avatar_total);
         avatar_progress.set_style(sty.clone());
 
         for i in 0..page {
             let params = format!("?category=authorId&page={}&search={}&status=both&ordering=none&platform=all&limit=36", i + 1, user_id);
-            let response = client.get(format!("{}{}", API_URL, params))
+            let response = client.get(format!("{}{}", config().ripper_url(SEARCH_PATH), params))
                 .header(USER_AGENT, ua)
                 .send()
-                .expect("리퍼 스토어 데이터 요청 오류");
+                .expect(&tr!("ripper.request_error"));
             if response.status().is_success() {
-                let avatars = json["avatars"].as_array().expect("리퍼 스토어 데이터 형식 변환 오류");
+                let json: Value = serde_json::from_str(&*response.text().expect(&tr!("ripper.read_error"))).expect(&tr!("ripper.parse_error"));
+                let avatars = json["avatars"].as_array().expect(&tr!("ripper.convert_error"));
                 let mut idents = Vec::new();
 
                 for avatar in avatars {
-                    let avatar: SearchData = serde_json::from_value(avatar.clone()).expect("리퍼 스토어 데이터 해석 오류");
+                    let avatar: SearchData = serde_json::from_value(avatar.clone()).expect(&tr!("ripper.parse_error"));
                     idents.push(avatar.ident);
                 }
 
                 for ident in idents {
-                    let response = client.get(format!("{}?ident={}", API_DETAIL_URL, ident))
+                    let response = client.get(format!("{}?ident={}", config().ripper_url(DETAIL_PATH), ident))
                         .header(USER_AGENT, ua)
                         .send()
-                        .expect("리퍼 스토어 데이터 요청 오류");
+                        .expect(&tr!("ripper.request_error"));
                     if response.status().is_success() {
-                        let rr = response.text().unwrap();
-                        println!("{}", rr.clone());
-                        let json: AvatarItem = serde_json::from_str(&*rr).expect("리퍼 스토어 데이터 해석 오류");
+                        let body = response.text().expect(&tr!("ripper.read_error"));
+                        debug!("ripper detail ident={} bytes={}", ident, body.len());
+                        let json: AvatarItem = serde_json::from_str(&body).expect(&tr!("ripper.parse_error"));
 
                         // 생성 날짜가 없으면 검색할 수 없으므로 건너뛰기
-                        if json.pc.created.is_some() {
-                            // 처음 뜯긴 시간에서 뒤로 1분 범위
-                            let base_time = convert_time(json.pc.created.unwrap() - 300000);
+                        if let Some(created) = json.pc.created {
+                            // 처음 뜯긴 시점에 있던 사람들 등록
+                            credit_leak(&ident, &json.name, ripper_time(created));
 
-                            // 처음 뜯긴 시간에서 앞으로 1분 범위
-                            let range_time = convert_time(json.pc.created.unwrap() + 300000);
-
-                            // 뜯긴 시점에 있던 사람들 등록
-                            put(base_time, range_time);
-
-                            if json.pc.lastUpdated.is_some() {
-                                // 마지막으로 뜯긴 시간에서 뒤로 1분 범위
-                                let base_time = convert_time(json.pc.lastUpdated.unwrap() - 300000);
-
-                                // 마지막으로 뜯긴 시간에서 뒤로 1분 범위
-                                let range_time = convert_time(json.pc.lastUpdated.unwrap() + 300000);
-
-                                // 뜯긴 시점에 있던 사람들 등록
-                                put(base_time, range_time);
+                            if let Some(updated) = json.pc.lastUpdated {
+                                // 마지막으로 뜯긴 시점에 있던 사람들 등록
+                                credit_leak(&ident, &json.name, ripper_time(updated));
                             }
                         }
 
                         avatar_progress.inc(1);
+                    } else {
+                        warn!("ripper detail failed ident={} status={}", ident, response.status());
                     }
                 }
             }
         }
         avatar_progress.finish_and_clear();
     }
-
-    if get_ripper().is_empty() {
-        println!("검색된 리퍼 유저 데이터가 없습니다.");
-        println!("뜯긴 아바타는 있는데 검색되지 않은 경우는 VRCX 사용 이전에 뜯겼거나, 리퍼 스토어가 업데이트 되기 전에 뜯겨서 날짜가 기록되지 않은 경우입니다.");
-    }
-
-    let checked = config_dir().unwrap().join("VRCX/Anti-Ripper/store_check.txt");
-    fs::write(checked, "VRCX 데이터를 사용하여 리퍼 스토어에서 뜯긴 아바타를 모두 계산 했다는 확인 파일").expect("리퍼 스토어 검사 확인 파일 쓰기 오류");
-
-    Ok(())
 }
 
 fn search_old_logs() -> Result<(), Box<dyn std::error::Error>> {
-    let database_path = config_dir().unwrap().join("VRCX/VRCX.sqlite3");
-
-    let conn = Connection::open(database_path).expect("VRCX 데이터베이스 오류");
-    let mut stmt = conn.prepare("SELECT created_at, display_name, user_id FROM gamelog_join_leave WHERE type='OnPlayerJoined'").expect("데이터베이스 쿼리 오류");
+    let conn = history::open();
+    let mut stmt = conn.prepare("SELECT created_at, display_name, user_id FROM gamelog_join_leave WHERE type='OnPlayerJoined'").expect(&tr!("db.query_error"));
     let mut ready_count = 0;
     let mut data_list: Vec<UserData> = vec![];
     let parse_data = stmt.query_map([], |row| {
         Ok(UserData {
-            created_at: row.get(0).expect("데이터베이스에서 created_at 값 읽기 오류"),
-            display_name: row.get(1).expect("데이터베이스 display_name 값 읽기 오류"),
-            user_id: row.get(2).expect("데이터베이스 user_id 값 읽기 오류"),
+            created_at: row.get(0).expect(&tr!("db.read_created_at")),
+            display_name: row.get(1).expect(&tr!("db.read_display_name")),
+            user_id: row.get(2).expect(&tr!("db.read_user_id")),
         })
-    }).expect("데이터베이스 쿼리 실행 오류");
+    }).expect(&tr!("db.execute_error"));
 
     for user in parse_data {
-        data_list.push(user.expect("쿼리 결과 오류"));
+        data_list.push(user.expect(&tr!("db.row_error")));
     }
 
     for _ in data_list.clone().into_iter() {
         ready_count += 1;
     }
 
-    let style = ProgressStyle::with_template("{msg}\n[{elapsed_precise}] {wide_bar:.cyan/blue} {pos}/{len}").expect("진행바 구문 오류").progress_chars("#>-");
+    let style = ProgressStyle::with_template("{msg}\n[{elapsed_precise}] {wide_bar:.cyan/blue} {pos}/{len}").expect(&tr!("common.progress_style_error")).progress_chars("#>-");
     let pb = ProgressBar::new(ready_count);
     pb.set_style(style);
 
     let mut checked = vec![];
 
-    println!("프로그램이 VRCX 데이터에서 누락된 사용자 ID를 추가 하고 있습니다.");
+    println!("{}", tr!("scan.started"));
     let user_list = get_user();
 
     let mut already_count = 0;
     for v in user_list {
         checked.push(v.display_name);
         already_count += 1;
-        pb.set_message(format!("이미 저장된 데이터를 확인하는 중... {}", already_count));
+        pb.set_message(tr!("scan.checking_saved", already_count));
     }
 
     let mut stack = 0;
-    let max_size = 8;
+    let max_size = config().timing.bulk_size;
     let mut stack_list: Vec<String> = vec![];
 
     for value in data_list.into_iter() {
-        pb.set_message(format!("{} 유저 데이터 다운로드중...", &value.display_name.replace("\u{2028}", "").replace("\u{2029}", "")));
+        pb.set_message(tr!("scan.downloading", &value.display_name.replace("\u{2028}", "").replace("\u{2029}", "")));
 
         if checked.iter().find(|a| a.to_string() == value.display_name).is_some() {
-            pb.set_message(format!("{} 이미 다운로드됨", &value.display_name.replace("\u{2028}", "").replace("\u{2029}", "")));
+            pb.set_message(tr!("scan.already_downloaded", &value.display_name.replace("\u{2028}", "").replace("\u{2029}", "")));
             pb.inc(1);
         } else {
             if value.clone().user_id.is_empty() {
-                let database_path = config_dir().unwrap().join("VRCX/VRCX.sqlite3");
-                let conn = Connection::open(database_path).expect("VRCX 데이터베이스 오류");
+                let conn = history::open();
 
                 if stack != max_size && (pb.length().unwrap() - pb.position() > max_size) {
                     stack_list.push(value.display_name.clone());
@@ -362,17 +360,17 @@ fn search_old_logs() -> Result<(), Box<dyn std::error::Error>> {
 
                     let mut user_list = get_user();
                     if value.display_name.clone() == json[0]["displayName"] && user_list.iter().find(|a| a.display_name == value.display_name).is_none() {
-                        let mut select_query = conn.prepare(&format!("SELECT created_at FROM gamelog_join_leave WHERE display_name = {}", json[0]["displayName"])).expect("데이터베이스 쿼리 오류");
+                        let mut select_query = conn.prepare(&format!("SELECT created_at FROM gamelog_join_leave WHERE display_name = {}", json[0]["displayName"])).expect(&tr!("db.query_error"));
                         let result = select_query.query_map([], |row| {
                             Ok(UserData {
-                                created_at: row.get(0).expect("데이터베이스에서 created_at 값 읽기 오류"),
+                                created_at: row.get(0).expect(&tr!("db.read_created_at")),
                                 display_name: json[0]["displayName"].to_string().replace("\"", ""),
                                 user_id: json[0]["id"].to_string().replace("\"", ""),
                             })
-                        }).expect("데이터베이스 쿼리 실행 오류");
+                        }).expect(&tr!("db.execute_error"));
 
                         for data in result {
-                            user_list.push(data.expect("쿼리 결과 오류"));
+                            user_list.push(data.expect(&tr!("db.row_error")));
                             break;
                         }
 
@@ -395,17 +393,17 @@ fn search_old_logs() -> Result<(), Box<dyn std::error::Error>> {
                     for i in 0..(max_size - 1) as usize {
                         let mut user_list = get_user();
                         if value.display_name.clone() == json[i]["displayName"] && user_list.iter().find(|a| a.display_name == value.display_name).is_none() {
-                            let mut select_query = conn.prepare(&format!("SELECT created_at FROM gamelog_join_leave WHERE display_name = {}", json[i]["displayName"])).expect("데이터베이스 쿼리 오류");
+                            let mut select_query = conn.prepare(&format!("SELECT created_at FROM gamelog_join_leave WHERE display_name = {}", json[i]["displayName"])).expect(&tr!("db.query_error"));
                             let result = select_query.query_map([], |row| {
                                 Ok(UserData {
-                                    created_at: row.get(0).expect("데이터베이스에서 created_at 값 읽기 오류"),
+                                    created_at: row.get(0).expect(&tr!("db.read_created_at")),
                                     display_name: json[i]["displayName"].to_string().replace("\"", ""),
                                     user_id: json[i]["id"].to_string().replace("\"", ""),
                                 })
-                            }).expect("데이터베이스 쿼리 실행 오류");
+                            }).expect(&tr!("db.execute_error"));
 
                             for data in result {
-                                user_list.push(data.expect("쿼리 결과 오류"));
+                                user_list.push(data.expect(&tr!("db.row_error")));
                                 break;
                             }
 
@@ -429,14 +427,26 @@ fn search_old_logs() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    let ids = config_dir().unwrap().join("VRCX/Anti-Ripper/user_id_done.txt");
-    fs::write(ids, "모든 ID 확인이 끝났다는걸 확인하는 파일").expect("사용자 데이터 확인 완료 파일 쓰기 오류");
-    pb.finish_with_message("완료");
+    let ids = config().data_path("user_id_done.txt");
+    fs::write(ids, "모든 ID 확인이 끝났다는걸 확인하는 파일").expect(&tr!("scan.done_marker_error"));
+    pb.finish_with_message(tr!("common.done"));
 
     Ok(())
 }
 
-fn check_current_count(user_id: &str) -> bool {
+/// 로그인 한 모든 계정의 제작자 ID 로 리퍼 스토어를 확인하고, 계정마다 새로 뜯긴 아바타를 하나로 합친다
+fn check_all_accounts() -> Leaked {
+    let mut leaked = Leaked::default();
+    for (name, user_id) in profile::owned() {
+        let result = check_current_count(&name, &user_id);
+        leaked.avatars.extend(result.avatars);
+        leaked.again |= result.again;
+    }
+    leaked
+}
+
+/// 계정의 뜯긴 아바타 목록을 저장된 목록과 비교하여 새로 뜯긴 아바타 찾기
+fn check_current_count(profile_name: &str, user_id: &str) -> Leaked {
     fn set_params(page: u32, user_id: &str) -> [(String, String); 7] {
         return [
             ("category".to_string(), "authorid".to_string()),
@@ -449,497 +459,887 @@ fn check_current_count(user_id: &str) -> bool {
         ];
     }
 
-    // 파일에 저장할 정보
-    let count: u32;
-    let mut idents: Vec<String> = vec![];
-    let mut avatar_list = vec![];
-
     // 리퍼 스토어에게 안걸리도록 무작위 User-Agent 전송
     let client = Client::new();
     let ua = spoof_ua();
 
-    let response = client.get(API_URL)
+    let response = client.get(config().ripper_url(SEARCH_PATH))
         .form(&set_params(1, user_id))
         .header(USER_AGENT, ua)
         .send()
-        .expect("리퍼 스토어 데이터 요청 오류");
-    if response.status().is_success() {
-        let data: AvatarList = serde_json::from_str(&*response.text().expect("리퍼 스토어 데이터 읽기 오류")).expect("리퍼 스토어 데이터 형식 캐스트 오류");
-        count = data.count;
-
-        // 아바타가 1개라도 뜯겼을 경우
-        if data.count != 0 {
-            // 모든 페이지를 돌아가며 아바타의 ident 값을 확인한다
-            for page in 1..=data.pages {
-                let response = client.get(API_URL)
-                    .form(&set_params(page, user_id))
-                    .header(USER_AGENT, ua)
-                    .send()
-                    .expect("리퍼 스토어 데이터 요청 오류");
-                if response.status().is_success() {
-                    let data: AvatarList = serde_json::from_str(&*response.text().expect("리퍼 스토어 데이터 읽기 오류")).expect("리퍼 스토어 데이터 형식 캐스트 오류");
-                    let avatars = data.avatars;
-
-                    for avatar in avatars.iter() {
-                        idents.push(avatar.ident.clone());
-
-                        // 모든 ident 값을 돌아가며 확인한다.
-                        let idents_clone = idents.clone();
-                        let cpu_thread = available_parallelism().expect("CPU 스레드 개수 읽기 오류").get();
-                        let pool = ThreadPoolBuilder::new().num_threads(cpu_thread).build().expect("스레드 풀 생성 오류");
-
-                        // 브챗 서버와 달리 리퍼 스토어는 제한이 없으므로 멀티 스레드로 한꺼번에 긁어오자
-                        pool.install(|| {
-                            for ident in idents_clone {
-                                let client = Client::new();
-                                let response = client.get(API_DETAIL_URL)
-                                    .form(&[("ident", ident)])
-                                    .header(USER_AGENT, ua)
-                                    .send()
-                                    .expect("리퍼 스토어 데이터 요청 오류");
-                                if response.status().is_success() {
-                                    let data: AvatarItem = serde_json::from_str(&*response.text().expect("리퍼 스토어 데이터 읽기 오류")).expect("리퍼 스토어 데이터 해석 오류");
-                                    let name = data.name;
-                                    let created = data.pc.created;
-                                    let added = data.pc.dateAdded;
-                                    let updated = data.pc.lastUpdated;
-                                    let result = AvatarData { name, created, added, updated };
-                                    avatar_list.push(result);
-                                } else {}
-                            }
-                        })
-                    }
-                }
-            }
-        }
+        .expect(&tr!("ripper.request_error"));
+    if !response.status().is_success() {
+        warn!("ripper search failed user_id={} status={}", user_id, response.status());
+        return Leaked::default();
+    }
+    let data: AvatarList = serde_json::from_str(&*response.text().expect(&tr!("ripper.read_error"))).expect(&tr!("ripper.cast_error"));
+    let count = data.count;
 
-        // 저장 되어있는걸 불러오고 비교하기
-        let path = config_dir().unwrap().join("VRCX/Anti-Ripper/save.json");
-        if !path.clone().exists() {
-            File::create(path.clone()).expect("파일 생성 오류");
-        } else {
-            let data: SaveData = serde_json::from_reader(File::open(path.clone()).expect("파일 읽기 오류")).expect("JSON 구문 해석 오류");
-            if count != data.count {
-                println!("아바타가 새로 뜯겼습니다.");
-                true;
-            } else {
-                for value in data.avatar_list {
-                    if value.updated != avatar_list.iter().find(|a| a.name == value.name).expect("JSON 파싱 오류").updated {
-                        println!("{} 아바타가 또 뜯겼습니다.", value.name);
-                        true;
-                    }
-                }
-            }
+    // 모든 페이지를 돌아가며 아바타의 ident 값을 확인한다
+    let mut idents: Vec<String> = vec![];
+    for page in 1..=data.pages {
+        let response = client.get(config().ripper_url(SEARCH_PATH))
+            .form(&set_params(page, user_id))
+            .header(USER_AGENT, ua)
+            .send()
+            .expect(&tr!("ripper.request_error"));
+        if response.status().is_success() {
+            let data: AvatarList = serde_json::from_str(&*response.text().expect(&tr!("ripper.read_error"))).expect(&tr!("ripper.cast_error"));
+            idents.extend(data.avatars.into_iter().map(|a| a.ident));
         }
-
-        // 저장
-        let save = SaveData { count, idents, avatar_list };
-        let file = File::open(path).expect("파일 읽기 오류");
-        serde_json::to_writer(file, &save).expect("JSON 파일 쓰기 오류");
     }
 
-    false
+    // 브챗 서버와 달리 리퍼 스토어는 제한이 없으므로 멀티 스레드로 한꺼번에 긁어오자
+    let cpu_thread = available_parallelism().expect(&tr!("common.cpu_count_error")).get();
+    let pool = ThreadPoolBuilder::new().num_threads(cpu_thread).build().expect(&tr!("common.thread_pool_error"));
+    let current: Vec<AvatarRecord> = pool.install(|| {
+        idents.par_iter().filter_map(|ident| {
+            let response = Client::new().get(config().ripper_url(DETAIL_PATH))
+                .form(&[("ident", ident)])
+                .header(USER_AGENT, ua)
+                .send()
+                .expect(&tr!("ripper.request_error"));
+            if !response.status().is_success() {
+                warn!("ripper detail failed ident={} status={}", ident, response.status());
+                return None;
+            }
+            let item: AvatarItem = serde_json::from_str(&*response.text().expect(&tr!("ripper.read_error"))).expect(&tr!("ripper.parse_error"));
+            Some(avatars::from_item(ident, &item))
+        }).collect()
+    });
+
+    // 저장 되어있는걸 불러오고 비교하기
+    avatars::update(profile_name, count, current)
 }
 
 fn print_author() {
     println!("Anti-ripper {} / {} / {}", build::PKG_VERSION, build::RUST_VERSION, build::BUILD_OS);
-    println!("빌드 날짜: {}", build::BUILD_TIME);
+    println!("{}", tr!("author.build_date", build::BUILD_TIME));
     println!();
-    println!("제작자: 키에르");
+    println!("{}", tr!("author.author"));
     println!("Github: https://github.com/kieaer/Anti-Ripper");
-    println!("배포: https://arca.live/b/vrchat/84892729")
+    println!("{}", tr!("author.distribution"))
 }
 
-fn auto_update() -> Result<(), Box<dyn std::error::Error>> {
-    let client = Client::new();
-    let response = client.get("https://api.github.com/repos/kieaer/Anti-ripper/releases/latest")
-        .header(USER_AGENT, PROGRAM_USER_AGENT)
-        .send()
-        .expect("Github 연결 오류");
-    if response.status().is_success() {
-        let release: Value = response.json().expect("JSON 파싱 오류");
-        let tag_name = release["tag_name"].as_str().unwrap_or("Unknown");
-        let description = release["body"].as_str().unwrap_or("No description available");
-
-        if tag_name != build::PKG_VERSION {
+/// 새 버전이 있으면 변경 내용을 보여주고, 확인을 받은 뒤 설치하고 다시 실행
+fn auto_update(yes: bool) {
+    let release = match update::check() {
+        Ok(Some(release)) => release,
+        Ok(None) => {
+            println!("{}", tr!("update.up_to_date"));
             println!();
-            println!("{} 버전이 나왔습니다. (현재 {} 버전)", tag_name, build::PKG_VERSION);
+            return;
+        }
+        Err(e) => {
+            println!("{}", tr!("update.check_failed", e));
             println!();
-            println!("== 업데이트 내용");
-            println!("{}", description);
-
-            self_update::backends::github::Update::configure()
-                .repo_owner("Kieaer")
-                .repo_name("Anti-Ripper")
-                .bin_name("github")
-                .show_download_progress(true)
-                .current_version(cargo_crate_version!())
-                .target("")
-                .no_confirm(true)
-                .show_output(false)
-                .build()?
-                .update()?;
-
-            println!("업데이트 완료. 5초후 재시작 합니다.");
-            thread::sleep(Duration::from_secs(5));
-            let mut cmd = Command::new(std::env::current_exe().unwrap());
-            let args: Vec<String> = std::env::args().collect();
-            cmd.args(args.iter().skip(1));
-            match cmd.spawn() {
-                Ok(_) => {
-                    exit(0);
-                }
-                Err(_) => {
-                    exit(1);
-                }
-            }
-        } else {
-            println!("현재 최신 버전입니다.");
+            return;
         }
-    } else {
-        println!("업데이트 정보를 가져오는데 실패 했습니다.");
-    }
+    };
+
     println!();
-    Ok(())
+    println!("{}", tr!("update.available", release.version, build::PKG_VERSION));
+    println!();
+    println!("{}", tr!("update.notes"));
+    println!("{}", release.notes);
+    println!();
+
+    if !yes {
+        println!("{}", tr!("update.confirm"));
+        let answer: String = read!();
+        if answer.trim().to_lowercase() != "y" {
+            println!("{}", tr!("update.skipped"));
+            println!();
+            return;
+        }
+    }
+
+    if let Err(e) = update::install(&release) {
+        println!("{}", e);
+        println!();
+        return;
+    }
+
+    println!("{}", tr!("update.done"));
+    thread::sleep(Duration::from_secs(5));
+    let mut cmd = Command::new(current_exe().unwrap());
+    let args: Vec<String> = std::env::args().collect();
+    cmd.args(args.iter().skip(1));
+    match cmd.spawn() {
+        Ok(_) => {
+            exit(EXIT_OK);
+        }
+        Err(_) => {
+            exit(EXIT_FAILURE);
+        }
+    }
 }
 
-fn play_audio() {
-    let (_stream, stream_handle) = OutputStream::try_default().unwrap();
-    let file = BufReader::new(File::open("alert.wav").unwrap());
-    let source = Decoder::new(file).unwrap();
-    stream_handle.play_raw(source.convert_samples()).unwrap();
+fn update_command(check: bool, rollback: bool, yes: bool) -> i32 {
+    if rollback {
+        return match update::rollback() {
+            Ok(()) => {
+                println!("{}", tr!("update.rolled_back"));
+                EXIT_OK
+            }
+            Err(e) => {
+                println!("{}", e);
+                EXIT_FAILURE
+            }
+        };
+    }
+    if check {
+        return match update::check() {
+            Ok(Some(release)) => {
+                println!("{}", tr!("update.available", release.version, build::PKG_VERSION));
+                EXIT_OK
+            }
+            Ok(None) => {
+                println!("{}", tr!("update.up_to_date"));
+                EXIT_OK
+            }
+            Err(e) => {
+                println!("{}", e);
+                EXIT_FAILURE
+            }
+        };
+    }
+    auto_update(yes);
+    EXIT_OK
 }
 
-fn print_end() {
-    println!("== 공지 ==");
-    println!("드디어 2023년 10월 1일부로 리퍼 스토어가 문을 닫았습니다.");
-    println!("이제 이 프로그램은 더이상 사용되지 않으며, 이 문구 이후 이 프로그램은 더이상 실행되지 않습니다.");
-    println!("하지만 이것으로 리핑이 끝은 아닙니다.");
-    println!("여전히 개인 PC로 아바타를 뜯는 툴이 돌아다니고 있으며, 추후에 이러한 툴을 감지하는 툴의 원리를 알아낸다면 나중에라도 이 프로그램이 다시 살아날 수 있습니다.");
-    println!("그럼 행복한 아바타 제작을 하시길 바랍니다!");
-    println!();
+fn is_ready(path: &str) -> bool {
+    profile::active_path(path).exists()
+}
 
-    let itself = std::env::current_exe().unwrap();
-    let parent = itself.parent().unwrap();
-    self_replace::self_delete_outside_path(&parent).unwrap();
-    fs::remove_dir_all(&parent).unwrap();
+/// 명령을 실행하기 전에 필요한 작업이 끝났는지 확인
+fn require(files: &[(&str, &str)]) -> Result<(), i32> {
+    if !history::available() {
+        println!("{}", tr!("command.vrcx_missing"));
+        return Err(EXIT_NOT_READY);
+    }
+    for (file, command) in files {
+        if !is_ready(file) {
+            println!("{}", tr!("command.run_first", command));
+            return Err(EXIT_NOT_READY);
+        }
+    }
+    Ok(())
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    print_author();
-    print_end();
+/// 로그인 된 user_id 값을 확인하고 파일로 저장
+fn fetch_user_id() {
+    let auth_token = profile::active_path("auth");
+    let user_id = profile::active_path("user_id.txt");
 
-    auto_update().expect("업데이트 확인 오류");
+    let mut file = File::open(auth_token).expect(&tr!("io.open_error"));
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).expect(&tr!("io.read_error"));
 
-    fs::create_dir_all(config_dir().unwrap().join("VRCX/Anti-Ripper")).expect("폴더 생성 오류");
+    let client = Client::new();
+    let mut headers = HeaderMap::new();
+    headers.insert(USER_AGENT, PROGRAM_USER_AGENT.parse().unwrap());
+    headers.insert(COOKIE, contents.parse().unwrap());
+    let response = client.get(config().vrchat_url(LOGIN_PATH))
+        .headers(headers)
+        .send()
+        .expect(&tr!("login.server_failed"));
+    if response.status().is_success() {
+        let body = response.text().unwrap();
+        let json: Value = serde_json::from_str(&*body).expect(&tr!("json.parse_error"));
+        fs::write(user_id, json["id"].as_str().unwrap()).expect(&tr!("io.write_error"));
+    }
+}
 
-    let auth_token = config_dir().unwrap().join("VRCX/Anti-Ripper/auth");
-    let user_id = config_dir().unwrap().join("VRCX/Anti-Ripper/user_id.txt");
-    let user_json = config_dir().unwrap().join("VRCX/Anti-Ripper/user_id_done.txt");
-    let checked = config_dir().unwrap().join("VRCX/Anti-Ripper/store_check.txt");
-    let version = config_dir().unwrap().join("VRCX/Anti-Ripper/updated.txt");
-    let database = config_dir().unwrap().join("VRCX/VRCX.sqlite3");
+fn watch(plain: bool, no_input: bool) {
+    if plain || no_input {
+        watch::run_console(no_input);
+    } else {
+        dashboard::run().expect(&tr!("watch.dashboard_error"));
+    }
+}
 
-    if (!version.exists() && checked.exists()) || (version.exists() && checked.exists() && fs::read_to_string(version.clone()).unwrap() == "1") {
-        fs::remove_file(config_dir().unwrap().join("VRCX/Anti-Ripper/store_check.txt")).expect("파일 삭제 오류");
-        fs::remove_file(config_dir().unwrap().join("VRCX/Anti-Ripper/ripper.json")).expect("파일 삭제 오류");
-        fs::write(version, "2").expect("파일 쓰기 오류");
+fn print_rippers(min: u32) {
+    let ripper_json = get_ripper();
+    let user_json = get_user();
+    let allowlist = Allowlist::load();
+    let mut found = false;
+    for value in ripper_json {
+        if value.count != 0 && value.count >= min && !allowlist.contains(&value.name) {
+            found = true;
+            let line = if let Some(user) = user_json.iter().find(|a| a.display_name == value.name) {
+                tr!("common.name_id_count", value.name, user.user_id, value.count)
+            } else {
+                tr!("common.name_count", value.name, value.count)
+            };
+            match &value.verdict {
+                Some(verdict) => println!("{} {}", line, verdict.summary()),
+                None => println!("{}", line),
+            }
+        }
     }
+    if !found {
+        println!("{}", tr!("list.empty"))
+    }
+}
 
-    if !database.exists() {
-        panic!("VRCX 가 설치되지 않았습니다. 프로그램 종료됨.");
+/// 뜯길 때 같이 있던 비율이 평소보다 높은 순서로 출력. min 은 같이 있던 뜯김 수
+fn print_significance(min: u32) {
+    let report = significance::analyze(&worlds::get_leaks());
+    println!("{}", tr!("significance.summary", report.leaks, format!("{:.1}", report.session_seconds as f64 / 3600.0)));
+    let allowlist = Allowlist::load();
+    let rippers = get_ripper();
+    let players: Vec<_> = report.players.iter().filter(|a| a.present as u32 >= min.max(1) && !allowlist.contains(&a.name)).collect();
+    if players.is_empty() {
+        println!("{}", tr!("list.empty"));
+        return;
     }
+    for player in players {
+        let verdict = rippers.iter().find(|a| a.name == player.name).and_then(|a| a.verdict.as_ref()).map(|a| format!(" {}", a.summary())).unwrap_or_default();
+        println!("{}{}", tr!("significance.player", player.name, format!("{:.2e}", player.p_value), format!("{:.1}", player.lift)), verdict);
+        println!("{}", tr!("significance.detail", player.present, player.leaks, format!("{:.1}", player.rate * 100.0), format!("{:.1}", player.baseline * 100.0), format!("{:.1}", player.copresent_seconds as f64 / 3600.0), player.sessions, format!("{:.1}", player.likelihood_ratio)));
+    }
+}
 
-    // 자동 로그인을 위해 계정 정보 가져오기
-    if !auth_token.exists() {
-        login();
+/// 유저 이름 또는 usr_ ID 로 유저 찾기
+fn find_user(query: &str) -> Option<(String, Option<String>)> {
+    let user_json = get_user();
+    let query = query.trim();
+    if query.starts_with("usr_") {
+        return user_json.into_iter().find(|a| a.user_id == query).map(|a| (a.display_name, Some(a.user_id)));
     }
+    let user_id = user_json.iter().find(|a| a.display_name.eq_ignore_ascii_case(query)).map(|a| a.user_id.clone());
+    if user_id.is_some() || get_ripper().iter().any(|a| a.name.eq_ignore_ascii_case(query)) {
+        let name = user_json.iter().map(|a| a.display_name.clone()).chain(get_ripper().into_iter().map(|a| a.name))
+            .find(|a| a.eq_ignore_ascii_case(query))
+            .unwrap_or(query.to_string());
+        return Some((name, user_id));
+    }
+    None
+}
 
-    // VRCX 에서 누락된 데이터를 찾고 추가하기
-    if !user_json.exists() {
-        search_old_logs().expect("VRCX 데이터 검색 오류");
+/// 유저 상세 정보. `show` 명령과 감시 화면에서 같이 사용
+fn describe_user(name: &str) -> Vec<String> {
+    let user_id = get_user().into_iter().find(|a| a.display_name == name).map(|a| a.user_id);
+    let ripper = get_ripper().into_iter().find(|a| a.name == name);
+    let count = ripper.as_ref().map(|a| a.count).unwrap_or(0);
+
+    let mut lines = vec![
+        tr!("show.name", name),
+        format!("ID: {}", user_id.clone().unwrap_or(tr!("common.unknown"))),
+        tr!("show.count", count),
+    ];
+    if Allowlist::load().contains(name) {
+        lines.push(tr!("show.allowlisted"));
+    }
+    if let Some(verdict) = ripper.as_ref().and_then(|a| a.verdict.as_ref()) {
+        lines.push(tr!("show.verdict", verdict.kind.label(), format_time(verdict.date)));
+        if !verdict.note.is_empty() {
+            lines.push(tr!("show.verdict_note", verdict.note));
+        }
+        let new_evidence = ripper.as_ref().map_or(0, |a| a.new_evidence());
+        if new_evidence > 0 {
+            lines.push(tr!("show.verdict_new_evidence", new_evidence));
+        }
     }
 
-    // 로그인 된 user_id 값을 확인하고 파일로 저장
-    if !user_id.exists() {
-        let mut file = File::open(auth_token.clone()).expect("파일 열기 오류");
-        let mut contents = String::new();
-        file.read_to_string(&mut contents).expect("파일 읽기 오류");
+    // 외부 점수는 로컬 감지 횟수와 섞지 않고 따로 표시
+    if let Some(external) = user_id.as_deref().and_then(find_external) {
+        lines.push(tr!("show.external", external.score(), external.sources.len()));
+        for source in &external.sources {
+            let verified = if source.verified { tr!("show.verified") } else { tr!("show.unsigned") };
+            lines.push(tr!("show.external_source", source.source, source.score, verified, source.file));
+        }
+    }
 
-        let client = Client::new();
-        let mut headers = HeaderMap::new();
-        headers.insert(USER_AGENT, PROGRAM_USER_AGENT.parse().unwrap());
-        headers.insert(COOKIE, contents.parse().unwrap());
-        let response = client.get(LOGIN_URL)
-            .headers(headers)
-            .send()
-            .expect("브챗 서버 로그인 실패");
-        if response.status().is_success() {
-            let body = response.text().unwrap();
-            let json: Value = serde_json::from_str(&*body).expect("JSON 파싱 실패");
-            fs::write(user_id.clone(), json["id"].as_str().unwrap()).expect("파일 쓰기 실패");
+    let mut events = ripper.map(|a| a.events).unwrap_or_default();
+    if events.is_empty() {
+        if count != 0 {
+            lines.push(tr!("show.no_evidence"));
         }
+        return lines;
     }
 
-    // 리퍼 스토어에서 정보 확인
-    if auth_token.exists() && user_json.exists() && user_id.exists() && !checked.exists() {
-        get_info_from_ripper(&get_id()).expect("리퍼 스토어 정보 확인 실패");
+    events.sort_by_key(|a| a.leak_time);
+    lines.push(String::new());
+    lines.push(tr!("show.evidence"));
+    for (i, event) in events.iter().enumerate() {
+        lines.push(tr!("show.evidence_avatar", i + 1, event.avatar_name));
+        lines.push(tr!("show.leak_time", format_time(event.leak_time)));
+        lines.push(tr!("show.joined_at", format_time(event.joined_at), format!("{:+}", event.delta)));
+        if let Some(left_at) = event.left_at {
+            lines.push(tr!("show.left_at", format_time(left_at)));
+        }
+        if event.overlap > 0 {
+            lines.push(tr!("show.overlap", event.overlap));
+        }
+        lines.push(tr!("show.location", if event.location.is_empty() { tr!("common.unknown") } else { event.location.clone() }));
+        if !event.world_name.is_empty() {
+            lines.push(tr!("show.world", event.world_name));
+        }
     }
+    lines
+}
 
-    if auth_token.exists() && user_json.exists() && user_id.exists() && checked.exists() {
-        fn is_process_running(target_process_name: &str) -> bool {
-            let snapshot = unsafe { CreateToolhelp32Snapshot(0x00000002, 0) };
-
-            if snapshot != ptr::null_mut() {
-                let mut entry: PROCESSENTRY32 = PROCESSENTRY32 {
-                    dwSize: std::mem::size_of::<PROCESSENTRY32>() as DWORD,
-                    cntUsage: 0,
-                    th32ProcessID: 0,
-                    th32DefaultHeapID: 0,
-                    th32ModuleID: 0,
-                    cntThreads: 0,
-                    th32ParentProcessID: 0,
-                    pcPriClassBase: 0,
-                    dwFlags: 0,
-                    szExeFile: [0; MAX_PATH],
-                };
+fn show_user(query: &str) -> i32 {
+    let Some((name, _)) = find_user(query) else {
+        println!("{}", tr!("show.not_found", query));
+        return EXIT_FAILURE;
+    };
+    for line in describe_user(&name) {
+        println!("{}", line);
+    }
+    EXIT_OK
+}
 
-                if unsafe { Process32First(snapshot, &mut entry) } != 0 {
-                    loop {
-                        let process_name = entry.szExeFile.iter()
-                            .take_while(|&&c| c != 0)
-                            .map(|&c| c as u8 as char)
-                            .collect::<String>();
+/// 출력할 월드 이름. 이름을 모르면 월드 ID, 그것도 모르면 알 수 없음
+fn world_label(world_name: &str, world_id: &str) -> String {
+    match (world_name.is_empty(), world_id.is_empty()) {
+        (false, _) => world_name.to_string(),
+        (true, false) => world_id.to_string(),
+        (true, true) => tr!("common.unknown"),
+    }
+}
 
-                        if process_name.to_lowercase() == target_process_name.to_lowercase() {
-                            return true;
-                        }
+fn print_worlds(instances: bool) -> i32 {
+    let worlds = worlds::summarize(&worlds::get_leaks());
+    if worlds.is_empty() {
+        println!("{}", tr!("worlds.empty"));
+        return EXIT_OK;
+    }
+    for world in worlds {
+        println!("{}", tr!("worlds.world", world_label(&world.world_name, &world.world_id), world.leaks, world.instances.len()));
+        if !world.players.is_empty() {
+            let players: Vec<String> = world.players.iter().take(5).map(|(name, count)| tr!("worlds.player", name, count)).collect();
+            println!("{}", tr!("worlds.players", players.join(", ")));
+        }
+        if !instances {
+            continue;
+        }
+        for instance in &world.instances {
+            println!("{}", tr!("worlds.instance", if instance.location.is_empty() { tr!("common.unknown") } else { instance.location.clone() }));
+            for (avatar_name, leak_time) in &instance.leaks {
+                println!("{}", tr!("worlds.leak", format_time(*leak_time), avatar_name));
+            }
+            if !instance.players.is_empty() {
+                println!("{}", tr!("worlds.co_present", instance.players.join(", ")));
+            }
+        }
+    }
+    EXIT_OK
+}
 
-                        if unsafe { Process32Next(snapshot, &mut entry) } == 0 {
-                            break;
-                        }
-                    }
+fn ingest_logs(dir: &Path) -> i32 {
+    let results = match history::ingest_dir(dir) {
+        Ok(results) => results,
+        Err(e) => {
+            println!("{}", e);
+            return EXIT_FAILURE;
+        }
+    };
+    if results.is_empty() {
+        println!("{}", tr!("ingest.no_files", dir.display()));
+        return EXIT_FAILURE;
+    }
+    let mut failed = false;
+    for (name, result) in results {
+        match result {
+            Ok(a) => println!("{}", tr!("ingest.file", name, a.joins, a.leaves, a.locations)),
+            Err(e) => {
+                failed = true;
+                println!("{}", e);
+            }
+        }
+    }
+    println!("{}", tr!("ingest.done"));
+    if failed { EXIT_FAILURE } else { EXIT_OK }
+}
+
+fn set_verdict(query: &str, kind: Option<VerdictKind>, note: Option<String>, date: Option<NaiveDate>, remove: bool) -> i32 {
+    // 감지 기록이 없는 유저도 이름으로 판단을 남길 수 있다
+    let name = find_user(query).map(|(name, _)| name).unwrap_or_else(|| query.trim().to_string());
+    if remove {
+        if verdict::remove(&name) {
+            println!("{}", tr!("verdict.removed", name));
+            return EXIT_OK;
+        }
+        println!("{}", tr!("verdict.not_found", name));
+        return EXIT_FAILURE;
+    }
+    let Some(kind) = kind else {
+        match get_ripper().into_iter().find(|a| a.name == name).and_then(|a| a.verdict) {
+            Some(verdict) => {
+                println!("{}", tr!("show.verdict", verdict.kind.label(), format_time(verdict.date)));
+                if !verdict.note.is_empty() {
+                    println!("{}", tr!("show.verdict_note", verdict.note));
                 }
+                return EXIT_OK;
+            }
+            None => {
+                println!("{}", tr!("verdict.not_found", name));
+                return EXIT_FAILURE;
+            }
+        }
+    };
+    let date = date.and_then(|a| a.and_hms_opt(0, 0, 0))
+        .and_then(|a| Local.from_local_datetime(&a).earliest())
+        .map_or_else(|| Utc::now().timestamp_millis(), |a| a.timestamp_millis());
+    let verdict = verdict::set(&name, kind, note.unwrap_or_default(), date);
+    println!("{}", tr!("verdict.saved", name, verdict.kind.label()));
+    EXIT_OK
+}
+
+/// 브챗 친구 목록을 허용 목록으로 가져오기. 실패해도 계속 진행한다
+fn sync_friends() {
+    match allowlist::sync_friends() {
+        Ok(count) => println!("{}", tr!("allow.synced", count)),
+        Err(e) => {
+            warn!("friends sync failed: {}", e);
+            println!("{}", e);
+        }
+    }
+}
+
+fn print_allowlist() {
+    let list = allowlist::get_allowlist();
+    if list.is_empty() {
+        println!("{}", tr!("allow.empty"));
+        return;
+    }
+    for entry in list {
+        let name = if entry.display_name.is_empty() { tr!("common.unknown") } else { entry.display_name };
+        let source = match entry.source {
+            allowlist::AllowSource::Manual => tr!("allow.source_manual"),
+            allowlist::AllowSource::Friend => tr!("allow.source_friend"),
+        };
+        println!("{}", tr!("allow.entry", entry.user_id, name, source, format_time(entry.added)));
+        if !entry.note.is_empty() {
+            println!("{}", tr!("allow.note", entry.note));
+        }
+    }
+}
 
-                unsafe { winapi::um::handleapi::CloseHandle(snapshot) };
+/// 모든 계정의 뜯긴 아바타와 기록 출력
+fn print_avatars() -> i32 {
+    let mut found = false;
+    for name in profile::list() {
+        let Some(data) = avatars::load(&name) else { continue };
+        for avatar in data.avatars {
+            found = true;
+            println!("{}", tr!("avatars.name", avatar.name, avatar.ident, name));
+            println!("{}", tr!("avatars.first_seen", format_time(avatar.first_seen)));
+            if let Some(created) = avatar.created {
+                println!("{}", tr!("avatars.created", format_time(created)));
             }
+            if !avatar.updates.is_empty() {
+                let updates: Vec<String> = avatar.updates.iter().map(|a| format_time(*a)).collect();
+                println!("{}", tr!("avatars.updates", updates.join(", ")));
+            }
+            println!("{}", tr!("avatars.platforms", avatar.platforms.join(", "), avatar.unity_version));
+            if !avatar.listed {
+                println!("{}", tr!("avatars.unlisted"));
+            }
+        }
+    }
+    if !found {
+        println!("{}", tr!("avatars.empty"));
+    }
+    EXIT_OK
+}
 
-            false
+fn export(output: Option<PathBuf>, format: Option<Format>, filter: Filter, source: Option<String>) -> i32 {
+    let format = format.or_else(|| output.as_deref().and_then(Format::from_path)).unwrap_or(Format::Json);
+    if source.is_some() && format != Format::Json {
+        println!("{}", tr!("export.sign_json_only"));
+        return EXIT_USAGE;
+    }
+    let key = source.as_ref().map(|_| signing_key());
+    let text = export::render(format, &filter, source.as_deref().zip(key.as_ref()));
+
+    match output {
+        Some(path) => {
+            if let Err(e) = fs::write(&path, text) {
+                println!("{}", tr!("io.write_path_error", path.display(), e));
+                return EXIT_FAILURE;
+            }
+            println!("{}", tr!("export.saved", path.display()));
         }
+        None => println!("{}", text),
+    }
+    EXIT_OK
+}
 
-        thread::spawn(|| {
-            loop {
-                loop {
-                    if is_process_running("VRChat.exe") {
-                        break;
-                    }
+fn import_list(file: PathBuf, source: Option<String>, allow_unsigned: bool) -> i32 {
+    match external::import(&file, source, allow_unsigned) {
+        Ok(result) => {
+            println!("{}", tr!("external.imported", result.added, result.updated, result.skipped));
+            EXIT_OK
+        }
+        Err(e) => {
+            println!("{}", e);
+            EXIT_FAILURE
+        }
+    }
+}
 
-                    thread::sleep(Duration::from_secs(60));
-                }
+fn make_bundle(query: &str, output: Option<PathBuf>) -> i32 {
+    let Some((name, _)) = find_user(query) else {
+        println!("{}", tr!("show.not_found", query));
+        return EXIT_FAILURE;
+    };
+    let bundle = match bundle::create(&name) {
+        Ok(bundle) => bundle,
+        Err(e) => {
+            println!("{}", e);
+            return EXIT_FAILURE;
+        }
+    };
+    let output = output.unwrap_or(PathBuf::from(format!("{}.bundle.json", name.replace(|c: char| !c.is_alphanumeric(), "_"))));
+    if let Err(e) = bundle::write(&bundle, &output) {
+        println!("{}", e);
+        return EXIT_FAILURE;
+    }
+    println!("{}", tr!("bundle.saved", bundle.body.events.len(), bundle.body.join_rows.len(), bundle.body.log_lines.len(), output.display()));
+    println!("{}", tr!("bundle.public_key", bundle.public_key));
+    EXIT_OK
+}
 
-                let dir_path = home_dir().unwrap().join("AppData\\LocalLow\\VRChat\\VRChat");
-                let specific_word = "output_log";
-                let mut path: String = String::new();
-                let mut waiting_count = 0;
+fn verify_bundle(file: PathBuf) -> i32 {
+    let bundle = match bundle::read(&file) {
+        Ok(bundle) => bundle,
+        Err(e) => {
+            println!("{}", e);
+            return EXIT_FAILURE;
+        }
+    };
+    let problems = bundle::verify(&bundle);
+    if !problems.is_empty() {
+        for problem in problems {
+            println!("- {}", problem);
+        }
+        println!("{}", tr!("bundle.verify_failed"));
+        return EXIT_FAILURE;
+    }
+    println!("{}", tr!("bundle.verified", bundle.body.name, bundle.body.events.len(), bundle.public_key));
+    EXIT_OK
+}
 
-                while path.is_empty() {
-                    thread::sleep(Duration::from_secs(1));
-                    // 5분동안 로그 읽기
-                    if waiting_count == 300 {
-                        println!("로그를 읽는데 실패 했습니다. 브챗 데이터 폴더 위치를 이동했거나 브챗을 켜는데 걸린 시간이 너무 길었습니다.");
-                        break;
-                    } else {
-                        waiting_count += 1;
-                    }
+fn print_external(min: u32) {
+    let mut list = get_external();
+    list.retain(|a| a.score() >= min);
+    list.sort_by_key(|a| Reverse(a.score()));
+    if list.is_empty() {
+        println!("{}", tr!("external.empty"));
+    }
+    for data in list {
+        let sources: Vec<String> = data.sources.iter().map(|a| tr!("external.source_row", a.source, a.score, if a.verified { String::new() } else { tr!("external.unsigned_tag") })).collect();
+        println!("{}", tr!("external.row", data.name, data.user_id, data.score(), sources.join(", ")));
+    }
+}
 
-                    let mut matching_files: Vec<fs::DirEntry> = Vec::new();
-                    let entries = fs::read_dir(dir_path.clone()).unwrap();
+fn remove_data(files: &[&str]) {
+    for file in files {
+        let path = config().data_path(file);
+        if path.exists() {
+            fs::remove_file(path).expect(&tr!("io.remove_error"));
+        }
+    }
+}
 
-                    for entry in entries {
-                        let entry = entry.unwrap();
-                        let path = entry.path();
-                        let file_name = path.file_name().unwrap().to_string_lossy().to_lowercase();
+/// reset --all 에서도 남겨두는 설정, 서명 키, 기록 파일과 되돌리기용 실행 파일
+const KEEP_ON_RESET: &[&str] = &["config.toml", "signing_key", "alerts.log", "serve.log", "anti-ripper.log", "previous.exe"];
 
-                        if file_name.contains(specific_word) {
-                            matching_files.push(entry);
-                        }
-                    }
+fn uninstall(yes: bool, remove_exe: bool) -> i32 {
+    let mut files: Vec<PathBuf> = config().data_files().iter().map(|a| config().data_path(a)).collect();
+    files.push(config().data_dir().join(MANIFEST_FILE));
+    if let Some(path) = &config().alerts.log_file {
+        files.push(path.clone());
+    }
+    if let Some(path) = &config().serve.log_file {
+        files.push(path.clone());
+    }
+    files.extend(logging::log_files());
+    // 계정 파일은 계정 폴더에 있으므로 목록 파일과 따로 찾는다
+    for name in profile::list() {
+        files.extend(profile::PROFILE_FILES.iter().map(|a| profile::path(&name, a)));
+    }
+    let profiles: Vec<String> = profile::list().into_iter().filter(|a| a != profile::DEFAULT_PROFILE).collect();
+    // 목록에는 profiles 같은 폴더 이름도 들어있다
+    files.retain(|a| a.is_file());
+    files.sort();
+    files.dedup();
+
+    if files.is_empty() && !remove_exe {
+        println!("{}", tr!("uninstall.nothing"));
+        return EXIT_OK;
+    }
+    println!("{}", tr!("uninstall.list"));
+    for file in &files {
+        println!("  {}", file.display());
+    }
+    if remove_exe {
+        println!("  {}", current_exe().expect(&tr!("uninstall.exe_path_error")).display());
+    }
 
-                    matching_files.sort_by(|a, b| {
-                        let a_metadata = a.metadata().unwrap();
-                        let b_metadata = b.metadata().unwrap();
-                        b_metadata.modified().unwrap().cmp(&a_metadata.modified().unwrap())
-                    });
+    if !yes {
+        println!("{}", tr!("uninstall.confirm"));
+        let answer: String = read!();
+        if answer.trim().to_lowercase() != "y" {
+            println!("{}", tr!("uninstall.cancelled"));
+            return EXIT_FAILURE;
+        }
+    }
 
-                    if let Some(_) = matching_files.first() {
-                        for file in matching_files.iter().skip(1) {
-                            let file_path = file.path();
-                            fs::remove_file(&file_path).expect("파일 삭제 오류");
-                        }
-                    }
+    for file in &files {
+        if let Err(e) = fs::remove_file(file) {
+            println!("{}", tr!("uninstall.remove_failed", file.display(), e));
+        }
+    }
+    // 사용자가 넣어둔 다른 파일이 있으면 폴더는 남겨둔다
+    for name in &profiles {
+        let _ = fs::remove_dir(profile::dir(name));
+    }
+    let _ = fs::remove_dir(config().data_path("profiles"));
+    let _ = fs::remove_dir(config().data_dir());
 
-                    if let Ok(entries) = fs::read_dir(dir_path.clone()) {
-                        let mut earliest_creation_time: Option<SystemTime> = None;
-                        let mut earliest_file_path: Option<String> = None;
-
-                        for entry in entries {
-                            if let Ok(entry) = entry {
-                                if let Some(file_name) = entry.file_name().to_str() {
-                                    if file_name.contains(specific_word) {
-                                        let metadata = entry.metadata().unwrap();
-                                        if let Ok(creation_time) = metadata.created() {
-                                            if earliest_creation_time.is_none() || creation_time < earliest_creation_time.unwrap() {
-                                                earliest_creation_time = Some(creation_time);
-                                                earliest_file_path = Some(entry.path().to_string_lossy().into_owned());
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
+    if remove_exe {
+        self_replace::self_delete().expect(&tr!("uninstall.exe_remove_error"));
+    }
+    println!("{}", tr!("uninstall.done"));
+    EXIT_OK
+}
 
-                        if let Some(file_path) = earliest_file_path {
-                            path = file_path;
-                            break;
-                        }
-                    }
-                }
+fn logout() -> i32 {
+    profile::remove(profile::active());
+    println!("{}", tr!("login.logged_out", profile::active()));
+    EXIT_OK
+}
 
+/// 계정 목록과 각 계정의 usr_ ID 출력
+fn print_profiles() -> i32 {
+    let profiles = profile::list();
+    if profiles.is_empty() {
+        println!("{}", tr!("profile.empty"));
+        return EXIT_OK;
+    }
+    for name in profiles {
+        let active = if name == profile::active() { "*" } else { " " };
+        let logged_in = if profile::path(&name, "auth").exists() { tr!("profile.logged_in") } else { tr!("profile.logged_out") };
+        let user_id = profile::user_id(&name).unwrap_or_else(|| tr!("common.unknown"));
+        println!("{} {} - {} ({})", active, name, user_id, logged_in);
+    }
+    EXIT_OK
+}
 
-                if !path.is_empty() {
-                    println!("로그 경로: {}", path.clone());
+fn reset(scan: bool, correlate: bool, all: bool) -> i32 {
+    if !scan && !correlate && !all {
+        println!("{}", tr!("reset.choose"));
+        return EXIT_USAGE;
+    }
+    if scan || all {
+        remove_data(&["user_id_done.txt"]);
+    }
+    if correlate || all {
+        remove_data(&["store_check.txt", "audit.jsonl", "leaks.json"]);
+        verdict::clear_evidence();
+    }
+    if all {
+        let files: Vec<String> = config().data_files().into_iter()
+            .filter(|a| !KEEP_ON_RESET.contains(&a.as_str()) && config().data_path(a).is_file())
+            .collect();
+        remove_data(&files.iter().map(String::as_str).collect::<Vec<&str>>());
+        for name in profile::list() {
+            profile::remove(&name);
+        }
+    }
+    println!("{}", tr!("reset.done"));
+    EXIT_OK
+}
 
-                    let m = MultiProgress::new();
-                    let mut file_size = 0;
+/// 하위 명령 없이 실행했을 때 전체 과정을 순서대로 진행
+fn run_all(no_update: bool) -> i32 {
+    if !no_update && config().update.check_on_start {
+        auto_update(false);
+    }
 
-                    let mut owner = String::new();
+    let auth_token = profile::active_path("auth");
+    let user_id = profile::active_path("user_id.txt");
+    let user_json = config().data_path("user_id_done.txt");
+    let checked = config().data_path("store_check.txt");
+    let version = config().data_path("updated.txt");
 
-                    loop {
-                        thread::sleep(Duration::from_millis(10));
+    if (!version.exists() && checked.exists()) || (version.exists() && checked.exists() && fs::read_to_string(version.clone()).unwrap() == "1") {
+        fs::remove_file(config().data_path("store_check.txt")).expect(&tr!("io.remove_error"));
+        fs::remove_file(config().data_path("ripper.json")).expect(&tr!("io.remove_error"));
+        fs::write(version, "2").expect(&tr!("io.write_error"));
+    }
 
-                        if !is_process_running("VRChat.exe") {
-                            println!("브챗 종료됨.");
-                            break;
-                        }
+    if let Err(code) = require(&[]) {
+        return code;
+    }
 
-                        let mut file = File::open(path.clone()).expect("로그 파일 열기 오류");
-                        let current_size = file.metadata().expect("로그 파일 데이터 읽기 오류").len();
-                        if current_size > file_size {
-                            file.seek(SeekFrom::Start(file_size)).expect("로그 파일 부분 읽기 오류");
-                            let new_content = io::BufReader::new(&file);
-                            for line in new_content.lines() {
-                                if let Ok(line_str) = line {
-                                    let owner_pattern = Regex::new(r"User Authenticated: (\w+) \(").unwrap();
-                                    if let Some(captures) = owner_pattern.captures(line_str.trim()) {
-                                        if let Some(username) = captures.get(1) {
-                                            owner = username.as_str().to_string();
-                                        }
-                                    }
-
-                                    let pattern = r"OnPlayerJoined\s+(\w+)";
-                                    let re = Regex::new(pattern).expect("정규식 패턴 오류");
-
-                                    if let Some(captures) = re.captures(line_str.trim()) {
-                                        if let Some(word_after) = captures.get(1) {
-                                            let target_name = word_after.clone().as_str().to_string();
-                                            if target_name != owner {
-                                                let pb = Arc::new(Mutex::new(m.add(ProgressBar::new(1))));
-                                                let pb_clone = Arc::clone(&pb);
-                                                let style = ProgressStyle::with_template("{spinner} {wide_msg}").unwrap().tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈ ");
-                                                pb.lock().unwrap().set_style(style);
-                                                pb.lock().unwrap().set_message(format!("{} - 유저 확인중...", target_name));
-
-                                                let file_json: Vec<UserData> = serde_json::from_str(&*fs::read_to_string(config_dir().unwrap().join("VRCX/Anti-ripper/user_id.json")).expect("파일 오류")).expect("JSON 구문 오류");
-                                                let exists = file_json.iter().find(|a| target_name == a.display_name);
-                                                if exists.is_none() {
-                                                    pb.lock().unwrap().set_message(format!("{} - 서버에서 검색중...", target_name));
-                                                    let json = get_info_from_server(line_str.as_str().to_string(), &pb.lock().unwrap());
-
-                                                    let database_path = config_dir().unwrap().join("VRCX/VRCX.sqlite3");
-                                                    let conn = Connection::open(database_path).expect("VRCX 데이터베이스 오류");
-
-                                                    let mut user_list: Vec<UserData> = serde_json::from_str(&*fs::read_to_string(config_dir().unwrap().join("VRCX/Anti-ripper/user_id.json")).expect("파일 오류")).expect("JSON 구문 오류");
-                                                    let mut select_query = conn.prepare(&format!("SELECT created_at FROM gamelog_join_leave WHERE display_name = {}", json[0]["displayName"])).expect("데이터베이스 쿼리 오류");
-                                                    let result = select_query.query_map([], |row| {
-                                                        Ok(UserData {
-                                                            created_at: row.get(0).expect("데이터베이스에서 created_at 값 읽기 오류"),
-                                                            display_name: json[0]["displayName"].to_string().replace("\"", ""),
-                                                            user_id: json[0]["id"].to_string().replace("\"", ""),
-                                                        })
-                                                    }).expect("데이터베이스 쿼리 실행 오류");
-
-                                                    for data in result {
-                                                        user_list.push(data.expect("쿼리 결과 오류"));
-                                                        break;
-                                                    }
-
-                                                    set_user(user_list);
-                                                } else {
-                                                    pb.lock().unwrap().set_message(format!("{} - 이미 등록된 유저", target_name));
-                                                }
-                                                pb.lock().unwrap().set_message(format!("{} - 확인중...", target_name));
-
-                                                thread::spawn(move || {
-                                                    let pb = pb_clone.lock().unwrap();
-                                                    let mut time = 0;
-                                                    for _ in 0..150 {
-                                                        thread::sleep(Duration::from_secs(1));
-                                                        time += 1;
-                                                        pb.set_message(format!("{} - 대기중... {}", target_name, time));
-                                                    }
-                                                    let result = check_current_count(&get_id());
-                                                    pb.finish_and_clear();
-
-                                                    if result {
-                                                        let mut json = get_ripper();
-                                                        let count = json.clone().iter().find(|a| a.name == target_name).expect("JSON 파싱 오류").count;
-
-                                                        if let Some(index) = json.iter().position(|a| a.name == target_name) {
-                                                            json[index].count += 1;
-                                                            set_ripper(json);
-                                                        }
-                                                        play_audio();
-                                                        println!("{} 유저가 입장했을 때 뜯겼습니다. 현재 이 유저의 감지 횟수는 {}회.", target_name, count + 1);
-                                                    }
-                                                });
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                            file_size = current_size;
-                        }
-                    }
+    // 자동 로그인을 위해 계정 정보 가져오기
+    if !auth_token.exists() {
+        login();
+    }
+
+    // VRCX 에서 누락된 데이터를 찾고 추가하기
+    if !user_json.exists() {
+        search_old_logs().expect(&tr!("scan.error"));
+    }
+
+    if !user_id.exists() {
+        fetch_user_id();
+    }
+
+    // 리퍼 스토어에서 정보 확인
+    if auth_token.exists() && user_json.exists() && user_id.exists() && !checked.exists() {
+        get_info_from_ripper().expect(&tr!("ripper.check_failed"));
+    }
+
+    if auth_token.exists() && user_json.exists() && user_id.exists() && checked.exists() {
+        watch(false, false);
+    }
+
+    EXIT_OK
+}
+
+fn run(command: Option<Commands>, no_update: bool) -> i32 {
+    match command {
+        None => run_all(no_update),
+        Some(Commands::Login { force }) => {
+            if force || !is_ready("auth") {
+                login();
+            }
+            fetch_user_id();
+            EXIT_OK
+        }
+        Some(Commands::Logout) => logout(),
+        Some(Commands::Profiles) => print_profiles(),
+        Some(Commands::Avatars) => print_avatars(),
+        Some(Commands::Worlds { instances }) => print_worlds(instances),
+        Some(Commands::Scan { force }) => {
+            if let Err(code) = require(&[("auth", "login")]) {
+                return code;
+            }
+            if force || !is_ready("user_id_done.txt") {
+                search_old_logs().expect(&tr!("scan.error"));
+            } else {
+                println!("{}", tr!("scan.already_done"));
+            }
+            EXIT_OK
+        }
+        Some(Commands::Correlate { force }) => {
+            if let Err(code) = require(&[("auth", "login"), ("user_id.txt", "login"), ("user_id_done.txt", "scan")]) {
+                return code;
+            }
+            if force || !is_ready("store_check.txt") {
+                if force {
+                    remove_data(&["leaks.json"]);
+                    verdict::clear_evidence();
                 }
+                get_info_from_ripper().expect(&tr!("ripper.check_failed"));
+            } else {
+                println!("{}", tr!("correlate.already_done"));
             }
-        });
-
-        println!("프로그램 종료를 할 때에는 그냥 닫으시면 됩니다.");
-        println!("a를 입력하여 카운트 확인.");
-
-        loop {
-            let command: String = read!();
-
-            if command == "a" {
-                let ripper_json = get_ripper();
-                let user_json = get_user();
-                let mut found = false;
-                for value in ripper_json {
-                    if value.count != 0 {
-                        found = true;
-                        if user_json.iter().find(|a| a.display_name == value.name).is_some() {
-                            println!("{}({}) - {}회", value.name, user_json.iter().find(|a| a.display_name == value.name).unwrap().user_id, value.count);
-                        } else {
-                            println!("{} - {}회", value.name, value.count);
-                        }
-                    }
+            EXIT_OK
+        }
+        Some(Commands::Watch { plain, no_input }) => {
+            if let Err(code) = require(&[("auth", "login"), ("user_id.txt", "login"), ("user_id_done.txt", "scan"), ("store_check.txt", "correlate")]) {
+                return code;
+            }
+            watch(plain, no_input);
+            EXIT_OK
+        }
+        Some(Commands::Serve { port }) => {
+            if let Err(code) = require(&[("auth", "login"), ("user_id.txt", "login"), ("user_id_done.txt", "scan"), ("store_check.txt", "correlate")]) {
+                return code;
+            }
+            match serve::run(port) {
+                Ok(()) => EXIT_OK,
+                Err(e) => {
+                    println!("{}", e);
+                    EXIT_FAILURE
                 }
-                if !found {
-                    println!("발견된 리퍼충이 없습니다.")
+            }
+        }
+        Some(Commands::List { min, by }) => {
+            match by.unwrap_or(config().scoring.ranking) {
+                Ranking::Count => print_rippers(min),
+                Ranking::Significance => print_significance(min),
+            }
+            EXIT_OK
+        }
+        Some(Commands::Show { user }) => show_user(&user),
+        Some(Commands::Export { output, format, min_score, since, until, sign: _, source }) => export(output, format, Filter { min_score, since, until }, source),
+        Some(Commands::Import { file, source, allow_unsigned }) => import_list(file, source, allow_unsigned),
+        Some(Commands::IngestLogs { dir }) => ingest_logs(&dir),
+        Some(Commands::Verdict { user, kind, note, date, remove }) => set_verdict(&user, kind, note, date, remove),
+        Some(Commands::Allow { user_id, note }) => match allowlist::add(&user_id, note) {
+            Ok(entry) => {
+                println!("{}", tr!("allow.added", entry.user_id, if entry.display_name.is_empty() { tr!("common.unknown") } else { entry.display_name }));
+                EXIT_OK
+            }
+            Err(e) => {
+                println!("{}", e);
+                EXIT_USAGE
+            }
+        },
+        Some(Commands::Disallow { user_id }) => {
+            if allowlist::remove(&user_id) {
+                println!("{}", tr!("allow.removed", user_id));
+                EXIT_OK
+            } else {
+                println!("{}", tr!("allow.not_found", user_id));
+                EXIT_FAILURE
+            }
+        }
+        Some(Commands::Allowlist { sync_friends: sync }) => {
+            if sync {
+                if let Err(code) = require(&[("auth", "login")]) {
+                    return code;
                 }
+                sync_friends();
             }
+            print_allowlist();
+            EXIT_OK
+        }
+        Some(Commands::External { min }) => {
+            print_external(min);
+            EXIT_OK
         }
+        Some(Commands::Bundle { user, output }) => make_bundle(&user, output),
+        Some(Commands::Verify { file }) => verify_bundle(file),
+        Some(Commands::Update { check, rollback, yes }) => update_command(check, rollback, yes),
+        Some(Commands::Uninstall { yes, remove_exe }) => uninstall(yes, remove_exe),
+        Some(Commands::Reset { scan, correlate, all }) => reset(scan, correlate, all),
     }
+}
 
-    Ok(())
-}
\ No newline at end of file
+fn main() {
+    // 도움말과 설정 오류도 읽을 수 있게 명령줄과 설정을 읽기 전에 언어부터 정하기
+    i18n::set_locale(cli::lang_arg().unwrap_or_else(i18n::system_locale));
+    let cli = cli::parse();
+
+    print_author();
+
+    // 설정 파일, 환경 변수, 명령줄 옵션 순서로 설정 불러오기
+    if let Err(errors) = config::init(&cli.overrides) {
+        println!("{}", tr!("config.errors"));
+        for error in errors {
+            println!("  {}", error);
+        }
+        exit(EXIT_USAGE);
+    }
+    i18n::set_locale(config().locale.unwrap_or_else(i18n::system_locale));
+
+    fs::create_dir_all(config().data_dir()).expect(&tr!("io.create_dir_error"));
+    if let Err(e) = profile::init(cli.profile) {
+        println!("{}", e);
+        exit(EXIT_USAGE);
+    }
+    logging::init();
+    info!("start version={} command={:?}", env!("CARGO_PKG_VERSION"), cli.command);
+
+    exit(run(cli.command, cli.no_update));
+}
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use rusqlite::Connection;
+
+use crate::bundle::JoinRow;
+use crate::library::{parse_vrcx_time, vrcx_time};
+
+/// 유저 한명이 나와 같은 인스턴스에 있던 시간
+#[derive(Debug)]
+pub struct Presence {
+    pub display_name: String,
+    pub location: String,
+    /// 입장 시간 (UTC 밀리초)
+    pub joined_at: i64,
+    /// 퇴장 시간 (UTC 밀리초). 퇴장 기록이 없으면 None
+    pub left_at: Option<i64>,
+    pub join_row: JoinRow,
+    pub leave_row: Option<JoinRow>,
+}
+
+impl Presence {
+    /// 주어진 범위와 겹치는 시간 (밀리초). 퇴장 기록이 없으면 범위 끝까지 있었던 것으로 본다
+    pub fn overlap(&self, start: i64, end: i64) -> i64 {
+        let left_at = self.left_at.unwrap_or(end).min(end);
+        (left_at - self.joined_at.max(start)).max(0)
+    }
+}
+
+/// VRCX 입장/퇴장 기록을 짝지어서 start ~ end 와 겹치는 시간들 만들기.
+/// lookback 만큼 앞뒤 기록까지 읽어서 범위 전에 들어와 있던 유저도 찾는다
+pub fn intervals(conn: &Connection, start: i64, end: i64, lookback: i64) -> Vec<Presence> {
+    let mut stmt = conn.prepare("SELECT created_at, type, display_name, location, user_id FROM gamelog_join_leave WHERE type IN ('OnPlayerJoined', 'OnPlayerLeft') AND created_at BETWEEN ?1 AND ?2 ORDER BY created_at").expect(&tr!("db.query_error"));
+    let rows = stmt.query_map([vrcx_time(start - lookback), vrcx_time(end + lookback)], |row| {
+        Ok(JoinRow {
+            created_at: row.get(0)?,
+            kind: row.get(1)?,
+            display_name: row.get(2)?,
+            location: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+            user_id: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+        })
+    }).expect(&tr!("db.execute_error"));
+
+    let mut open: HashMap<String, Presence> = HashMap::new();
+    let mut closed: Vec<Presence> = vec![];
+    for row in rows {
+        let row = row.expect(&tr!("db.row_error"));
+        let Some(time) = parse_vrcx_time(&row.created_at) else { continue };
+
+        // 다른 인스턴스의 기록이 나오면 내가 이동한 것이므로 이전 인스턴스에 있던 유저도 끝난 것으로 본다
+        let moved: Vec<String> = open.iter().filter(|(_, a)| a.location != row.location).map(|(a, _)| a.clone()).collect();
+        for name in moved {
+            let mut presence = open.remove(&name).unwrap();
+            presence.left_at = Some(time);
+            closed.push(presence);
+        }
+
+        if row.kind == "OnPlayerJoined" {
+            // 퇴장 기록 없이 다시 입장했으면 이전 기록은 다시 입장한 시간에 끝난 것으로 본다
+            if let Some(mut presence) = open.remove(&row.display_name) {
+                presence.left_at = Some(time);
+                closed.push(presence);
+            }
+            open.insert(row.display_name.clone(), Presence {
+                display_name: row.display_name.clone(),
+                location: row.location.clone(),
+                joined_at: time,
+                left_at: None,
+                join_row: row,
+                leave_row: None,
+            });
+        } else if let Some(mut presence) = open.remove(&row.display_name) {
+            presence.left_at = Some(time);
+            presence.leave_row = Some(row);
+            closed.push(presence);
+        }
+        // 입장 기록 없는 퇴장은 lookback 보다 먼저 들어온 것이라 언제부터 있었는지 알 수 없으므로 사용하지 않는다
+    }
+    closed.extend(open.into_values());
+
+    closed.into_iter().filter(|a| a.overlap(start, end) > 0).collect()
+}
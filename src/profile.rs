@@ -0,0 +1,100 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::config::config;
+
+/// 처음부터 쓰던 계정. 파일은 예전처럼 데이터 폴더에 바로 둔다
+pub const DEFAULT_PROFILE: &str = "default";
+/// 계정마다 따로 저장하는 파일
+pub const PROFILE_FILES: &[&str] = &["auth", "user_id.txt", "save.json"];
+const PROFILE_DIR: &str = "profiles";
+
+static ACTIVE: OnceLock<String> = OnceLock::new();
+
+/// 영문, 숫자, `-`, `_` 만 허용
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|a| a.is_ascii_alphanumeric() || a == '-' || a == '_')
+}
+
+/// 명령줄에서 고른 계정으로 설정. 한번만 호출한다
+pub fn init(name: Option<String>) -> Result<(), String> {
+    let name = name.unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    if !is_valid_name(&name) {
+        return Err(tr!("profile.invalid_name", name));
+    }
+    ACTIVE.set(name).map_err(|_| tr!("profile.already_initialized"))
+}
+
+pub fn active() -> &'static str {
+    ACTIVE.get().map_or(DEFAULT_PROFILE, |a| a.as_str())
+}
+
+pub fn dir(profile: &str) -> PathBuf {
+    if profile == DEFAULT_PROFILE {
+        config().data_dir()
+    } else {
+        config().data_path(PROFILE_DIR).join(profile)
+    }
+}
+
+/// 계정 파일 경로. 계정마다 따로 두지 않는 파일은 데이터 폴더 경로
+pub fn path(profile: &str, file: &str) -> PathBuf {
+    if PROFILE_FILES.contains(&file) {
+        dir(profile).join(file)
+    } else {
+        config().data_path(file)
+    }
+}
+
+/// 지금 사용 중인 계정의 파일 경로
+pub fn active_path(file: &str) -> PathBuf {
+    path(active(), file)
+}
+
+/// 로그인 했거나 user_id 가 있는 계정 목록. 기본 계정이 항상 처음
+pub fn list() -> Vec<String> {
+    let mut profiles = vec![];
+    if PROFILE_FILES.iter().any(|a| path(DEFAULT_PROFILE, a).exists()) {
+        profiles.push(DEFAULT_PROFILE.to_string());
+    }
+    if let Ok(entries) = fs::read_dir(config().data_path(PROFILE_DIR)) {
+        let mut names: Vec<String> = entries.flatten()
+            .filter(|a| a.path().is_dir())
+            .map(|a| a.file_name().to_string_lossy().to_string())
+            .filter(|a| is_valid_name(a) && a != DEFAULT_PROFILE)
+            .collect();
+        names.sort();
+        profiles.extend(names);
+    }
+    profiles
+}
+
+pub fn user_id(profile: &str) -> Option<String> {
+    fs::read_to_string(path(profile, "user_id.txt")).ok()
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty())
+}
+
+/// 리퍼 스토어에서 검색할 계정과 usr_ ID. 같은 ID 는 한번만
+pub fn owned() -> Vec<(String, String)> {
+    let mut owned: Vec<(String, String)> = vec![];
+    for profile in list() {
+        if let Some(id) = user_id(&profile) {
+            if !owned.iter().any(|(_, a)| *a == id) {
+                owned.push((profile, id));
+            }
+        }
+    }
+    owned
+}
+
+/// 계정 파일과 폴더 삭제. 기본 계정은 폴더를 남겨둔다
+pub fn remove(profile: &str) {
+    for file in PROFILE_FILES {
+        let _ = fs::remove_file(path(profile, file));
+    }
+    if profile != DEFAULT_PROFILE {
+        let _ = fs::remove_dir(dir(profile));
+    }
+}
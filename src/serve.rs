@@ -0,0 +1,333 @@
+use std::{fs, thread};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::allowlist::Allowlist;
+use crate::config::config;
+use crate::library::{format_time, get_ripper, get_user, now_millis};
+use crate::watch;
+use crate::worlds;
+use crate::watch::{WatchControl, WatchEvent};
+
+// 브라우저에서 보낸 요청은 이 헤더를 붙일 수 없어서 다른 웹 페이지가 제어하는 것을 막는다
+const CONTROL_HEADER: &str = "x-anti-ripper";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_HEADERS: usize = 64;
+const MAX_REQUEST_SIZE: u64 = 16 * 1024;
+
+/// 기록해 둔 감시 이벤트
+#[derive(Debug, Clone, Serialize)]
+struct EventRecord {
+    id: u64,
+    /// 이벤트가 생긴 시간 (UTC 밀리초)
+    time: i64,
+    message: Option<String>,
+    event: WatchEvent,
+}
+
+/// 감시 이벤트로 만든 현재 상태
+#[derive(Debug, Default, Serialize)]
+struct Status {
+    game_running: bool,
+    log_path: Option<String>,
+    roster: Vec<String>,
+    pending: BTreeMap<String, String>,
+    last_rescan: Option<i64>,
+}
+
+struct ServeState {
+    started_at: i64,
+    status: Mutex<Status>,
+    events: Mutex<VecDeque<EventRecord>>,
+    next_id: AtomicU64,
+    log: Mutex<File>,
+}
+
+impl ServeState {
+    fn record(&self, event: WatchEvent) {
+        let time = now_millis();
+        let message = event.message();
+
+        {
+            let mut status = self.status.lock().unwrap();
+            match &event {
+                WatchEvent::WaitingGame => status.game_running = false,
+                WatchEvent::LogOpened(path) => {
+                    status.game_running = true;
+                    status.log_path = Some(path.clone());
+                }
+                WatchEvent::LogMissing | WatchEvent::GameClosed => {
+                    status.game_running = false;
+                    status.roster.clear();
+                }
+                WatchEvent::RoomChanged => status.roster.clear(),
+                WatchEvent::Joined(name) => {
+                    if !status.roster.contains(name) {
+                        status.roster.push(name.clone());
+                    }
+                }
+                WatchEvent::Left(name) => status.roster.retain(|a| a != name),
+                WatchEvent::Checking { name, message } => {
+                    status.pending.insert(name.clone(), message.clone());
+                }
+                WatchEvent::Waiting { name, elapsed, total } => {
+                    status.pending.insert(name.clone(), tr!("watch.seconds_left", total.saturating_sub(*elapsed)));
+                }
+                WatchEvent::Checked { name, .. } => {
+                    status.pending.remove(name);
+                }
+                WatchEvent::Rescanned { .. } => status.last_rescan = Some(time),
+                WatchEvent::SuspectJoined { .. } | WatchEvent::AlertFailed(_) => {}
+            }
+        }
+
+        // 1초마다 오는 대기 이벤트는 상태에만 반영
+        if matches!(event, WatchEvent::Waiting { .. }) {
+            return;
+        }
+
+        if let Some(message) = &message {
+            self.write_log(time, message);
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut events = self.events.lock().unwrap();
+        events.push_back(EventRecord { id, time, message, event });
+        while events.len() > config().serve.max_events {
+            events.pop_front();
+        }
+    }
+
+    fn write_log(&self, time: i64, message: &str) {
+        let _ = writeln!(self.log.lock().unwrap(), "[{}] {}", format_time(time), message);
+    }
+}
+
+pub fn log_path() -> PathBuf {
+    config().serve.log_file.clone().unwrap_or_else(|| config().data_path("serve.log"))
+}
+
+/// 창 없이 감시를 계속하면서 제어 API 열기
+pub fn run(port: Option<u16>) -> Result<(), String> {
+    let settings = &config().serve;
+    let address = format!("{}:{}", settings.bind, port.unwrap_or(settings.port));
+    let listener = TcpListener::bind(&address).map_err(|e| tr!("serve.bind_error", address, e))?;
+
+    let path = log_path();
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let log = OpenOptions::new().create(true).append(true).open(&path).map_err(|e| tr!("alert.log_open_error", path.display(), e))?;
+
+    let state = Arc::new(ServeState {
+        started_at: now_millis(),
+        status: Mutex::new(Status::default()),
+        events: Mutex::new(VecDeque::new()),
+        next_id: AtomicU64::new(0),
+        log: Mutex::new(log),
+    });
+    state.write_log(now_millis(), &tr!("serve.started", address));
+
+    let (control, receiver) = watch::new_control();
+    watch::spawn(control.clone());
+    spawn_recorder(receiver, state.clone());
+    spawn_poller(control.clone());
+
+    println!("{}", tr!("serve.listening", address));
+    println!("{}", tr!("serve.log_file", path.display()));
+
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        let state = state.clone();
+        let control = control.clone();
+        thread::spawn(move || handle(stream, &state, &control));
+    }
+    Ok(())
+}
+
+fn spawn_recorder(receiver: Receiver<WatchEvent>, state: Arc<ServeState>) {
+    thread::spawn(move || {
+        for event in receiver {
+            state.record(event);
+        }
+    });
+}
+
+/// 유저가 입장하지 않아도 설정한 간격마다 리퍼 스토어 확인
+fn spawn_poller(control: WatchControl) {
+    let interval = config().serve.poll_interval;
+    if interval == 0 {
+        return;
+    }
+    thread::spawn(move || loop {
+        thread::sleep(Duration::from_secs(interval));
+        if !control.paused.load(Ordering::Relaxed) {
+            control.rescan();
+        }
+    });
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    headers: HashMap<String, String>,
+}
+
+fn read_request(stream: &TcpStream) -> Option<Request> {
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST_SIZE));
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query.split('&')
+        .filter_map(|a| a.split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+
+    let mut headers = HashMap::new();
+    for _ in 0..MAX_HEADERS {
+        let mut header = String::new();
+        reader.read_line(&mut header).ok()?;
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    Some(Request { method, path: path.to_string(), query, headers })
+}
+
+/// 로컬 주소로 열었으면 DNS rebinding 요청을 막기 위해 Host 도 로컬 주소만 허용. 다른 주소로 열 때는 토큰으로 막는다
+fn is_allowed_host(host: &str) -> bool {
+    if !config().serve.bind.parse::<IpAddr>().is_ok_and(|a| a.is_loopback()) {
+        return true;
+    }
+    let name = match host.rsplit_once(':') {
+        Some((name, port)) if !port.contains(']') => name,
+        _ => host,
+    };
+    name.eq_ignore_ascii_case("localhost") || name == "[::1]" || name.parse::<IpAddr>().is_ok_and(|a| a.is_loopback())
+}
+
+fn handle(mut stream: TcpStream, state: &ServeState, control: &WatchControl) {
+    let _ = stream.set_read_timeout(Some(REQUEST_TIMEOUT));
+    let (code, body) = match read_request(&stream) {
+        Some(request) => route(&request, state, control),
+        None => (400, json!({ "error": tr!("serve.bad_request") })),
+    };
+    let reason = match code {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        _ => "Method Not Allowed",
+    };
+    let body = body.to_string();
+    let _ = write!(stream, "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", code, reason, body.len(), body);
+}
+
+/// serve.token 이 있으면 Bearer 토큰이 같아야 한다. 비교 시간이 내용에 따라 달라지지 않게 끝까지 비교
+fn is_authorized(authorization: Option<&String>) -> bool {
+    let token = &config().serve.token;
+    if token.is_empty() {
+        return true;
+    }
+    let Some(value) = authorization.and_then(|a| a.strip_prefix("Bearer ")) else { return false };
+    value.len() == token.len() && value.bytes().zip(token.bytes()).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+fn route(request: &Request, state: &ServeState, control: &WatchControl) -> (u16, Value) {
+    if !request.headers.get("host").is_some_and(|a| is_allowed_host(a)) {
+        return (403, json!({ "error": tr!("serve.forbidden_host") }));
+    }
+    if !is_authorized(request.headers.get("authorization")) {
+        return (401, json!({ "error": tr!("serve.unauthorized") }));
+    }
+    if request.method == "POST" && !request.headers.contains_key(CONTROL_HEADER) {
+        return (403, json!({ "error": tr!("serve.missing_header", CONTROL_HEADER) }));
+    }
+
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/status") => (200, status(state, control)),
+        ("GET", "/suspects") => {
+            let min = request.query.get("min").and_then(|a| a.parse().ok()).unwrap_or(1);
+            (200, suspects(min))
+        }
+        ("GET", "/events") => {
+            let since = request.query.get("since").and_then(|a| a.parse().ok()).unwrap_or(0);
+            let limit = request.query.get("limit").and_then(|a| a.parse().ok()).unwrap_or(usize::MAX);
+            (200, events(state, since, limit))
+        }
+        ("GET", "/worlds") => (200, json!({ "worlds": worlds::summarize(&worlds::get_leaks()) })),
+        ("POST", "/pause") => (200, set_paused(state, control, true)),
+        ("POST", "/resume") => (200, set_paused(state, control, false)),
+        ("POST", "/rescan") => {
+            control.rescan();
+            (200, json!({ "ok": true }))
+        }
+        (_, "/status" | "/suspects" | "/events" | "/worlds" | "/pause" | "/resume" | "/rescan") => (405, json!({ "error": tr!("serve.method_not_allowed") })),
+        _ => (404, json!({ "error": tr!("serve.not_found") })),
+    }
+}
+
+fn status(state: &ServeState, control: &WatchControl) -> Value {
+    let status = state.status.lock().unwrap();
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "started_at": state.started_at,
+        "paused": control.paused.load(Ordering::Relaxed),
+        "muted": control.muted.load(Ordering::Relaxed),
+        "game_running": status.game_running,
+        "log_path": status.log_path,
+        "roster": status.roster,
+        "pending": status.pending,
+        "last_rescan": status.last_rescan,
+    })
+}
+
+fn suspects(min: u32) -> Value {
+    let users = get_user();
+    let allowlist = Allowlist::load();
+    let mut list: Vec<_> = get_ripper().into_iter().filter(|a| a.count != 0 && a.count >= min && !allowlist.contains(&a.name)).collect();
+    list.sort_by(|a, b| b.count.cmp(&a.count).then(a.name.cmp(&b.name)));
+    Value::Array(list.into_iter().map(|a| {
+        let user_id = users.iter().find(|b| b.display_name == a.name).map(|b| b.user_id.clone());
+        json!({
+            "name": a.name,
+            "user_id": user_id,
+            "count": a.count,
+            "verdict": a.verdict,
+            "last_leak": a.events.iter().map(|b| b.leak_time).max(),
+        })
+    }).collect())
+}
+
+fn events(state: &ServeState, since: u64, limit: usize) -> Value {
+    let events = state.events.lock().unwrap();
+    let list: Vec<&EventRecord> = events.iter().filter(|a| a.id > since).collect();
+    let skip = list.len().saturating_sub(limit);
+    json!(list[skip..])
+}
+
+fn set_paused(state: &ServeState, control: &WatchControl, paused: bool) -> Value {
+    control.paused.store(paused, Ordering::Relaxed);
+    state.write_log(now_millis(), &if paused { tr!("serve.paused") } else { tr!("serve.resumed") });
+    json!({ "paused": paused })
+}
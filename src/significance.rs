@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use clap::ValueEnum;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::config::config;
+use crate::history;
+use crate::library::now_millis;
+use crate::presence;
+use crate::worlds::LeakRecord;
+
+/// 의심 유저를 줄 세우는 기준
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Ranking {
+    /// 감지 횟수가 많은 순서
+    Count,
+    /// 평소에 같이 있던 비율보다 뜯길 때 같이 있던 비율이 얼마나 높은지 (p-value 가 작은 순서)
+    Significance,
+}
+
+/// 유저 한명의 뜯길 때 같이 있던 비율과 평소 같이 있던 비율 비교
+#[derive(Debug, Clone, Serialize)]
+pub struct Significance {
+    pub name: String,
+    /// 비교에 사용한 뜯김 수
+    pub leaks: usize,
+    /// 그 중 같이 있던 뜯김 수
+    pub present: usize,
+    /// 같이 있던 시간 (초)
+    pub copresent_seconds: i64,
+    /// 같이 있던 횟수 (입장 기록 수)
+    pub sessions: usize,
+    /// 아무 뜯김 범위 하나에 평소처럼 같이 있을 확률
+    pub baseline: f64,
+    /// present / leaks
+    pub rate: f64,
+    /// rate / baseline
+    pub lift: f64,
+    /// 이항 검정의 우도비 (관측 비율 / 평소 비율)
+    pub likelihood_ratio: f64,
+    /// 평소 비율이라면 이만큼 이상 같이 있을 확률 (단측 이항 검정)
+    pub p_value: f64,
+}
+
+/// 전체 비교 결과와 공통 표본 크기
+#[derive(Debug, Serialize)]
+pub struct Report {
+    /// 내가 게임에 있던 전체 시간 (초)
+    pub session_seconds: i64,
+    /// 입장 기록이 있는 기간 안의 뜯김 수
+    pub leaks: usize,
+    pub players: Vec<Significance>,
+}
+
+/// ln(a + b) 를 ln a, ln b 로 계산
+fn log_add(a: f64, b: f64) -> f64 {
+    if a == f64::NEG_INFINITY {
+        return b;
+    }
+    let (high, low) = if a > b { (a, b) } else { (b, a) };
+    high + (low - high).exp().ln_1p()
+}
+
+/// 확률 p 로 n 번 중 k 번 이상 나올 확률
+pub fn binomial_upper_tail(n: usize, k: usize, p: f64) -> f64 {
+    if k == 0 {
+        return 1.0;
+    }
+    if p <= 0.0 {
+        return 0.0;
+    }
+    if p >= 1.0 {
+        return 1.0;
+    }
+    // 아주 작은 확률도 0 이 되지 않도록 로그로 계산
+    let (ln_p, ln_q) = (p.ln(), (1.0 - p).ln());
+    let mut ln_pmf = n as f64 * ln_q;
+    let mut tail = f64::NEG_INFINITY;
+    for x in 0..=n {
+        if x >= k {
+            tail = log_add(tail, ln_pmf);
+        }
+        if x < n {
+            ln_pmf += ((n - x) as f64).ln() - ((x + 1) as f64).ln() + ln_p - ln_q;
+        }
+    }
+    tail.exp().min(1.0)
+}
+
+/// 관측 비율과 평소 비율의 이항 우도비
+fn likelihood_ratio(n: usize, k: usize, p: f64) -> f64 {
+    let rate = k as f64 / n as f64;
+    let ln = |a: f64, count: f64| if count == 0.0 { 0.0 } else { count * a.ln() };
+    let (k, rest) = (k as f64, (n - k.min(n)) as f64);
+    let observed = ln(rate, k) + ln(1.0 - rate, rest);
+    let expected = ln(p, k) + ln(1.0 - p, rest);
+    (observed - expected).exp()
+}
+
+/// gamelog_location 에 기록된 인스턴스마다 머문 시간의 합 (밀리초)
+fn session_millis(conn: &Connection) -> i64 {
+    conn.query_row("SELECT COALESCE(SUM(time), 0) FROM gamelog_location", [], |row| row.get(0)).unwrap_or(0)
+}
+
+/// VRCX 와 로그에서 가져온 모든 기록에서 유저마다 평소 같이 있던 시간을 구하고, 뜯김 기록과 비교
+pub fn analyze(leaks: &[LeakRecord]) -> Report {
+    let conn = history::open();
+    let now = now_millis();
+    let presences = presence::intervals(&conn, 0, now, 0);
+
+    // 퇴장 기록이 없으면 마지막 기록까지만 있었던 것으로 본다
+    let last = presences.iter().map(|a| a.left_at.unwrap_or(a.joined_at)).max().unwrap_or(0);
+    let first = presences.iter().map(|a| a.joined_at).min().unwrap_or(0);
+    let mut copresent: HashMap<&str, (i64, usize)> = HashMap::new();
+    let mut merged: Vec<(i64, i64)> = vec![];
+    for presence in &presences {
+        let duration = presence.overlap(presence.joined_at, last);
+        let entry = copresent.entry(presence.display_name.as_str()).or_default();
+        entry.0 += duration;
+        entry.1 += 1;
+        merged.push((presence.joined_at, presence.joined_at + duration));
+    }
+
+    // 위치 기록에 머문 시간이 없으면 누군가와 같이 있던 시간을 합쳐서 대신 사용
+    let mut total = session_millis(&conn);
+    if total <= 0 {
+        merged.sort();
+        let mut end = i64::MIN;
+        for (a, b) in merged {
+            total += (b - a.max(end)).max(0);
+            end = end.max(b);
+        }
+    }
+
+    // 입장 기록이 있는 기간의 뜯김만 비교한다
+    let covered: Vec<&LeakRecord> = leaks.iter().filter(|a| a.leak_time >= first && a.leak_time <= last).collect();
+    let window = 2 * config().leak_window_millis() as i64;
+    let mut players = vec![];
+    if total > 0 && !covered.is_empty() {
+        for (name, (duration, sessions)) in copresent {
+            let present = covered.iter().filter(|a| a.players.iter().any(|b| b == name)).count();
+            if present == 0 {
+                continue;
+            }
+            // 범위 하나는 한 순간이 아니라 leak_window 앞뒤 시간이므로, 들어올 때 마다 그 길이만큼 확률이 늘어난다
+            let baseline = ((duration + sessions as i64 * window) as f64 / total as f64).clamp(f64::MIN_POSITIVE, 1.0);
+            let rate = present as f64 / covered.len() as f64;
+            players.push(Significance {
+                name: name.to_string(),
+                leaks: covered.len(),
+                present,
+                copresent_seconds: duration / 1000,
+                sessions,
+                baseline,
+                rate,
+                lift: rate / baseline,
+                likelihood_ratio: likelihood_ratio(covered.len(), present, baseline),
+                p_value: binomial_upper_tail(covered.len(), present, baseline),
+            });
+        }
+    }
+    players.sort_by(|a, b| a.p_value.total_cmp(&b.p_value).then(b.present.cmp(&a.present)).then(a.name.cmp(&b.name)));
+    Report { session_seconds: total / 1000, leaks: covered.len(), players }
+}
@@ -0,0 +1,38 @@
+use std::fs;
+
+use base64::{Engine as _, engine::general_purpose};
+use ed25519_dalek::{Signer, SigningKey};
+use sha2::{Digest, Sha256};
+
+use crate::config::config;
+
+const KEY_FILE: &str = "signing_key";
+
+/// 데이터 폴더의 서명 키를 불러오고, 없으면 새로 만든다
+pub fn signing_key() -> SigningKey {
+    let path = config().data_path(KEY_FILE);
+    if let Ok(text) = fs::read_to_string(&path) {
+        let bytes = general_purpose::STANDARD.decode(text.trim()).expect(&tr!("signing.key_format_error"));
+        let bytes: [u8; 32] = bytes.try_into().expect(&tr!("signing.key_length_error"));
+        return SigningKey::from_bytes(&bytes);
+    }
+
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).expect(&tr!("signing.random_error"));
+    let key = SigningKey::from_bytes(&bytes);
+    fs::write(&path, general_purpose::STANDARD.encode(key.to_bytes())).expect(&tr!("signing.key_write_error"));
+    key
+}
+
+pub fn public_key(key: &SigningKey) -> String {
+    general_purpose::STANDARD.encode(key.verifying_key().to_bytes())
+}
+
+/// base64 로 인코딩한 서명
+pub fn sign(key: &SigningKey, payload: &[u8]) -> String {
+    general_purpose::STANDARD.encode(key.sign(payload).to_bytes())
+}
+
+pub fn sha256_hex(value: &[u8]) -> String {
+    Sha256::digest(value).iter().map(|a| format!("{:02x}", a)).collect()
+}
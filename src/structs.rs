@@ -2,6 +2,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::verdict::Verdict;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserData {
     pub created_at: String,
@@ -9,13 +11,50 @@ pub struct UserData {
     pub user_id: String,
 }
 
-#[derive(Serialize, Deserialize)]
+/// 리퍼 스토어 확인 결과. 뜯긴 아바타 이름과 이미 뜯겼던 아바타가 다시 올라온 것인지
+#[derive(Debug, Default)]
+pub struct Leaked {
+    pub avatars: Vec<String>,
+    pub again: bool,
+}
+
+/// 계정의 뜯긴 아바타 기록 (save.json)
+#[derive(Default, Serialize, Deserialize)]
 pub struct SaveData {
     pub count: u32,
-    pub idents: Vec<String>,
+    /// 리퍼 스토어 ident 로 구분한 아바타 기록
+    #[serde(default)]
+    pub avatars: Vec<AvatarRecord>,
+    /// 예전 형식. 이름으로만 구분해서 처음 한번 비교할 때만 사용한다
+    #[serde(default, skip_serializing)]
     pub avatar_list: Vec<AvatarData>,
 }
 
+/// 뜯긴 아바타 하나의 기록
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvatarRecord {
+    pub ident: String,
+    pub name: String,
+    /// 이 프로그램이 처음 확인한 시간 (UTC 밀리초)
+    pub first_seen: i64,
+    /// 리퍼 스토어에 처음 올라온 시간 (UTC 밀리초)
+    pub created: Option<i64>,
+    /// 다시 뜯겨서 갱신된 시간들 (UTC 밀리초)
+    #[serde(default)]
+    pub updates: Vec<i64>,
+    #[serde(default)]
+    pub platforms: Vec<String>,
+    #[serde(default)]
+    pub unity_version: String,
+    /// 마지막 확인 때 리퍼 스토어 목록에 있었는지
+    #[serde(default = "listed_default")]
+    pub listed: bool,
+}
+
+fn listed_default() -> bool {
+    true
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ParseData {
     display_name: String,
@@ -106,4 +145,33 @@ pub struct AvatarItem {
 pub struct RipperData {
     pub name: String,
     pub count: u32,
+    /// 감지 횟수를 올린 근거
+    #[serde(default)]
+    pub events: Vec<LeakEvent>,
+    /// 직접 확인한 뒤 내린 판단
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<Verdict>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeakEvent {
+    pub avatar_name: String,
+    pub avatar_id: String,
+    /// 뜯긴 시간 (UTC 밀리초)
+    pub leak_time: i64,
+    /// 입장 시간 (UTC 밀리초)
+    pub joined_at: i64,
+    /// 입장 시간 - 뜯긴 시간 (초)
+    pub delta: i64,
+    /// 입장했던 월드/인스턴스
+    pub location: String,
+    /// 퇴장 시간 (UTC 밀리초). 기록이 없거나 예전 기록이면 None
+    #[serde(default)]
+    pub left_at: Option<i64>,
+    /// 뜯긴 시간 범위와 같이 있던 시간이 겹친 길이 (초). 예전 기록은 0
+    #[serde(default)]
+    pub overlap: i64,
+    /// 뜯긴 시간에 내가 있던 월드 이름. VRCX 위치 기록이 없으면 빈 문자열
+    #[serde(default)]
+    pub world_name: String,
 }
\ No newline at end of file
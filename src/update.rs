@@ -0,0 +1,299 @@
+use std::env::current_exe;
+use std::fs;
+use std::path::PathBuf;
+
+use reqwest::blocking::Client;
+use reqwest::header::USER_AGENT;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::config;
+use crate::external::{decode_key, decode_signature};
+use crate::signing::sha256_hex;
+
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+// 업데이트 전에 실행 파일을 복사해 두는 곳
+const PREVIOUS_FILE: &str = "previous.exe";
+
+/// 업데이트 받을 릴리즈 종류
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    /// 정식 버전만
+    Stable,
+    /// 시험 버전(prerelease) 포함
+    Beta,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ReleaseInfo {
+    tag_name: String,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    assets: Vec<Asset>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Release {
+    pub version: Version,
+    pub notes: String,
+    assets: Vec<Asset>,
+}
+
+fn parse_version(tag: &str) -> Option<Version> {
+    Version::parse(tag.trim().trim_start_matches('v')).ok()
+}
+
+pub fn current_version() -> Version {
+    parse_version(CURRENT_VERSION).expect(&tr!("update.current_version_error"))
+}
+
+fn get(client: &Client, url: &str) -> Result<reqwest::blocking::Response, String> {
+    let response = client.get(url)
+        .header(USER_AGENT, format!("Anti-Ripper/{}", CURRENT_VERSION))
+        .send()
+        .map_err(|e| tr!("update.connect_error", e))?;
+    if response.status().is_success() { Ok(response) } else { Err(tr!("update.response_error", response.status(), url)) }
+}
+
+/// 설정한 릴리즈 주소와 채널로 새 버전 확인
+pub fn check() -> Result<Option<Release>, String> {
+    check_feed(&config().urls.github_release, config().update.channel, &current_version())
+}
+
+/// 릴리즈 목록에서 채널에 맞는 가장 높은 버전. current 보다 높을 때만 돌려준다
+fn check_feed(url: &str, channel: Channel, current: &Version) -> Result<Option<Release>, String> {
+    let client = Client::new();
+    let value: Value = get(&client, url)?.json().map_err(|e| tr!("update.parse_error", e))?;
+    // releases 목록 주소와 releases/latest 주소 둘 다 받는다
+    let list: Vec<ReleaseInfo> = if value.is_array() {
+        serde_json::from_value(value)
+    } else {
+        serde_json::from_value(value).map(|a| vec![a])
+    }.map_err(|e| tr!("update.parse_error", e))?;
+
+    let latest = list.into_iter()
+        .filter(|a| !a.draft)
+        .filter_map(|a| parse_version(&a.tag_name).map(|version| (version, a)))
+        .filter(|(version, a)| channel == Channel::Beta || (!a.prerelease && version.pre.is_empty()))
+        .filter(|(version, _)| version > current)
+        .max_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(latest.map(|(version, a)| Release { version, notes: a.body.unwrap_or_default(), assets: a.assets }))
+}
+
+fn find_asset<'a>(release: &'a Release, name: &str) -> Option<&'a Asset> {
+    release.assets.iter().find(|a| a.name.eq_ignore_ascii_case(name))
+}
+
+/// `<해시>  <파일 이름>` 형식의 체크섬 파일에서 해당 파일의 해시 찾기. 해시만 있는 파일도 받는다
+fn parse_checksum(text: &str, name: &str) -> Option<String> {
+    text.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let hash = parts.next()?;
+        match parts.next() {
+            Some(file) if file.trim_start_matches('*').eq_ignore_ascii_case(name) => Some(hash.to_lowercase()),
+            None => Some(hash.to_lowercase()),
+            _ => None,
+        }
+    })
+}
+
+/// 설정한 파일 이름과 공개키로 실행 파일 받기
+fn download(release: &Release) -> Result<Vec<u8>, String> {
+    let settings = &config().update;
+    download_asset(release, &settings.asset_name, &settings.public_key)
+}
+
+/// 실행 파일을 받아서 체크섬과 서명을 확인한다. 체크섬은 같은 릴리즈에서 받으므로 공개키가 없으면 설치하지 않는다
+fn download_asset(release: &Release, asset_name: &str, public_key: &str) -> Result<Vec<u8>, String> {
+    if public_key.trim().is_empty() {
+        return Err(tr!("update.public_key_missing"));
+    }
+    let key = decode_key(public_key)?;
+    let client = Client::new();
+
+    let asset = find_asset(release, asset_name).ok_or(tr!("update.asset_missing", asset_name))?;
+    let checksum_name = format!("{}.sha256", asset.name);
+    let checksum = find_asset(release, &checksum_name).or_else(|| find_asset(release, "SHA256SUMS"))
+        .ok_or(tr!("update.checksum_missing"))?;
+
+    let bytes = get(&client, &asset.browser_download_url)?.bytes().map_err(|e| tr!("update.download_error", e))?.to_vec();
+    let text = get(&client, &checksum.browser_download_url)?.text().map_err(|e| tr!("update.download_error", e))?;
+    let expected = parse_checksum(&text, &asset.name).ok_or(tr!("update.checksum_not_found"))?;
+    if sha256_hex(&bytes) != expected {
+        return Err(tr!("update.checksum_mismatch"));
+    }
+
+    let signature = find_asset(release, &format!("{}.sig", asset.name)).ok_or(tr!("update.signature_missing"))?;
+    let signature = get(&client, &signature.browser_download_url)?.text().map_err(|e| tr!("update.download_error", e))?;
+    key.verify_strict(&bytes, &decode_signature(&signature)?).map_err(|_| tr!("update.signature_mismatch"))?;
+
+    Ok(bytes)
+}
+
+pub fn previous_path() -> PathBuf {
+    config().data_path(PREVIOUS_FILE)
+}
+
+/// 새 실행 파일로 교체. 지금 실행 파일은 되돌리기 용으로 남겨둔다
+pub fn install(release: &Release) -> Result<(), String> {
+    let bytes = download(release)?;
+    let exe = current_exe().map_err(|e| tr!("update.exe_path_error", e))?;
+    fs::copy(&exe, previous_path()).map_err(|e| tr!("update.backup_error", e))?;
+
+    let new_path = config().data_path("update.exe");
+    fs::write(&new_path, bytes).map_err(|e| tr!("io.write_error_detail", e))?;
+    let result = self_replace::self_replace(&new_path).map_err(|e| tr!("update.replace_error", e));
+    let _ = fs::remove_file(&new_path);
+    result
+}
+
+/// 업데이트 전에 남겨둔 실행 파일로 되돌리기
+pub fn rollback() -> Result<(), String> {
+    let previous = previous_path();
+    if !previous.exists() {
+        return Err(tr!("update.no_previous"));
+    }
+    self_replace::self_replace(&previous).map_err(|e| tr!("update.replace_error", e))?;
+    fs::remove_file(&previous).map_err(|e| tr!("io.remove_error_detail", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use ed25519_dalek::SigningKey;
+    use serde_json::json;
+
+    use super::*;
+    use crate::signing::{public_key, sign};
+
+    /// 경로마다 정해둔 내용을 돌려주는 릴리즈 주소 대신 서버. 주소를 돌려준다
+    fn stand_in(files: Vec<(String, Vec<u8>)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                let mut reader = BufReader::new(stream);
+                let mut request = String::new();
+                let _ = reader.read_line(&mut request);
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                        break;
+                    }
+                }
+                let path = request.split_whitespace().nth(1).unwrap_or_default().to_string();
+                let mut stream = reader.into_inner();
+                match files.iter().find(|(a, _)| *a == path) {
+                    Some((_, body)) => {
+                        let _ = write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len());
+                        let _ = stream.write_all(body);
+                    }
+                    None => {
+                        let _ = write!(stream, "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+                    }
+                }
+            }
+        });
+        base
+    }
+
+    fn release(tag: &str, prerelease: bool) -> Value {
+        json!({ "tag_name": tag, "prerelease": prerelease, "body": tag, "assets": [] })
+    }
+
+    fn feed(releases: Value) -> String {
+        format!("{}/releases", stand_in(vec![("/releases".to_string(), releases.to_string().into_bytes())]))
+    }
+
+    fn version(value: &str) -> Version {
+        parse_version(value).unwrap()
+    }
+
+    #[test]
+    fn stable_ignores_beta() {
+        let url = feed(json!([release("v1.2.0", false), release("v1.3.0-beta.1", true), release("v1.4.0", true)]));
+        let stable = check_feed(&url, Channel::Stable, &version("1.0.0")).unwrap().unwrap();
+        assert_eq!(stable.version, version("1.2.0"));
+        let beta = check_feed(&url, Channel::Beta, &version("1.0.0")).unwrap().unwrap();
+        assert_eq!(beta.version, version("1.4.0"));
+    }
+
+    #[test]
+    fn no_downgrade() {
+        let url = feed(json!([release("v0.9.0", false), release("v1.0.0", false)]));
+        assert!(check_feed(&url, Channel::Stable, &version("1.0.0")).unwrap().is_none());
+        // releases/latest 처럼 하나만 돌려줘도 같다
+        let url = feed(release("v0.9.9", false));
+        assert!(check_feed(&url, Channel::Beta, &version("1.0.0")).unwrap().is_none());
+    }
+
+    #[test]
+    fn checksum_formats() {
+        let hash = "AB".repeat(32);
+        assert_eq!(parse_checksum(&format!("{}  Anti-Ripper.exe\n", hash), "Anti-Ripper.exe"), Some(hash.to_lowercase()));
+        assert_eq!(parse_checksum(&format!("{} *other.zip\n{} *anti-ripper.exe\n", "00".repeat(32), hash), "Anti-Ripper.exe"), Some(hash.to_lowercase()));
+        assert_eq!(parse_checksum(&format!("{}\n", hash), "Anti-Ripper.exe"), Some(hash.to_lowercase()));
+        assert_eq!(parse_checksum(&format!("{}  other.zip\n", hash), "Anti-Ripper.exe"), None);
+    }
+
+    /// 실행 파일, 체크섬, 서명을 올린 릴리즈
+    fn signed_release(binary: &[u8], checksum: &str, signature: &str) -> Release {
+        let base = stand_in(vec![
+            ("/app.exe".to_string(), binary.to_vec()),
+            ("/app.exe.sha256".to_string(), format!("{}  app.exe\n", checksum).into_bytes()),
+            ("/app.exe.sig".to_string(), signature.as_bytes().to_vec()),
+        ]);
+        let asset = |name: &str| Asset { name: name.to_string(), browser_download_url: format!("{}/{}", base, name) };
+        Release { version: version("9.0.0"), notes: String::new(), assets: vec![asset("app.exe"), asset("app.exe.sha256"), asset("app.exe.sig")] }
+    }
+
+    #[test]
+    fn verifies_download() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let binary = b"new binary".as_slice();
+        let release = signed_release(binary, &sha256_hex(binary), &sign(&key, binary));
+        assert_eq!(download_asset(&release, "app.exe", &public_key(&key)).unwrap(), binary);
+    }
+
+    #[test]
+    fn rejects_checksum_mismatch() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let binary = b"new binary".as_slice();
+        let release = signed_release(binary, &sha256_hex(b"other binary"), &sign(&key, binary));
+        assert_eq!(download_asset(&release, "app.exe", &public_key(&key)).unwrap_err(), tr!("update.checksum_mismatch"));
+    }
+
+    #[test]
+    fn rejects_signature_mismatch() {
+        let (key, other) = (SigningKey::from_bytes(&[7; 32]), SigningKey::from_bytes(&[8; 32]));
+        let binary = b"new binary".as_slice();
+        let release = signed_release(binary, &sha256_hex(binary), &sign(&other, binary));
+        assert_eq!(download_asset(&release, "app.exe", &public_key(&key)).unwrap_err(), tr!("update.signature_mismatch"));
+    }
+
+    #[test]
+    fn requires_public_key() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let binary = b"new binary".as_slice();
+        let release = signed_release(binary, &sha256_hex(binary), &sign(&key, binary));
+        assert_eq!(download_asset(&release, "app.exe", "").unwrap_err(), tr!("update.public_key_missing"));
+    }
+}
@@ -0,0 +1,105 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::library::{format_time, get_ripper, set_ripper};
+use crate::structs::RipperData;
+
+/// 직접 확인한 뒤 내린 판단
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum VerdictKind {
+    /// 리퍼가 맞음. 감지 횟수와 상관없이 입장하면 알림
+    Confirmed,
+    /// 리퍼가 아님. 새 근거가 생기기 전까지 알림을 보내지 않는다
+    Cleared,
+    /// 지켜보는 중. 감지 횟수와 상관없이 입장하면 알림
+    Watching,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Verdict {
+    pub kind: VerdictKind,
+    #[serde(default)]
+    pub note: String,
+    /// 판단한 날짜 (UTC 밀리초). 이 뒤에 뜯긴 근거는 새 근거로 본다
+    pub date: i64,
+}
+
+impl VerdictKind {
+    /// 내보내기에 쓰는 이름
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerdictKind::Confirmed => "confirmed",
+            VerdictKind::Cleared => "cleared",
+            VerdictKind::Watching => "watching",
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            VerdictKind::Confirmed => tr!("verdict.confirmed"),
+            VerdictKind::Cleared => tr!("verdict.cleared"),
+            VerdictKind::Watching => tr!("verdict.watching"),
+        }
+    }
+}
+
+impl Verdict {
+    /// 목록에 붙이는 짧은 설명
+    pub fn summary(&self) -> String {
+        tr!("verdict.summary", self.kind.label(), format_time(self.date).get(..10).unwrap_or_default())
+    }
+}
+
+impl RipperData {
+    /// 판단한 뒤에 뜯긴 감지 근거 수. 다시 계산해도 예전 근거는 새 근거가 되지 않는다
+    pub fn new_evidence(&self) -> usize {
+        self.verdict.as_ref().map_or(self.events.len(), |a| self.events.iter().filter(|b| b.leak_time > a.date).count())
+    }
+
+    /// 리퍼가 아니라고 판단했고 그 뒤로 새 근거가 없으면 알림을 보내지 않는다
+    pub fn is_cleared(&self) -> bool {
+        self.verdict.as_ref().is_some_and(|a| a.kind == VerdictKind::Cleared) && self.new_evidence() == 0
+    }
+
+    /// 감지 횟수와 상관없이 입장하면 알림을 보낼 유저
+    pub fn is_flagged(&self) -> bool {
+        self.verdict.as_ref().is_some_and(|a| a.kind != VerdictKind::Cleared)
+    }
+}
+
+/// 유저의 판단 저장. 감지 기록이 없는 유저도 판단만 남길 수 있다
+pub fn set(name: &str, kind: VerdictKind, note: String, date: i64) -> Verdict {
+    let mut list = get_ripper();
+    let index = match list.iter().position(|a| a.name == name) {
+        Some(index) => index,
+        None => {
+            list.push(RipperData { name: name.to_string(), count: 0, events: vec![], verdict: None });
+            list.len() - 1
+        }
+    };
+    let verdict = Verdict { kind, note, date };
+    list[index].verdict = Some(verdict.clone());
+    set_ripper(list);
+    verdict
+}
+
+/// 감지 횟수를 다시 계산할 때 감지 기록은 지우고 판단만 남긴다
+pub fn clear_evidence() {
+    let list: Vec<RipperData> = get_ripper().into_iter()
+        .filter_map(|a| a.verdict.map(|verdict| RipperData { name: a.name, count: 0, events: vec![], verdict: Some(verdict) }))
+        .collect();
+    set_ripper(list);
+}
+
+/// 판단을 지우면 true, 원래 없었으면 false
+pub fn remove(name: &str) -> bool {
+    let mut list = get_ripper();
+    let Some(ripper) = list.iter_mut().find(|a| a.name == name) else { return false };
+    if ripper.verdict.take().is_none() {
+        return false;
+    }
+    list.retain(|a| a.count != 0 || !a.events.is_empty() || a.verdict.is_some());
+    set_ripper(list);
+    true
+}
@@ -0,0 +1,492 @@
+use std::{fs, io, ptr, thread};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, Seek, SeekFrom};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use log::{debug, info};
+use regex::Regex;
+use serde::Serialize;
+use text_io::read;
+use winapi::shared::minwindef::{DWORD, MAX_PATH};
+use winapi::um::tlhelp32::{CreateToolhelp32Snapshot, Process32First, Process32Next, PROCESSENTRY32};
+
+use crate::{check_all_accounts, get_info_from_server, print_rippers};
+use crate::alert::{Alert, Alerter, LeakInfo, Severity, SuspectInfo};
+use crate::bundle::log_line_time;
+use crate::allowlist::Allowlist;
+use crate::config::config;
+use crate::history;
+use crate::correlate::credit_live;
+use crate::library::{format_time, get_ripper, get_user, now_millis, set_user, vrcx_time};
+use crate::structs::UserData;
+use crate::worn;
+
+/// 감시 스레드가 화면 쪽으로 보내는 이벤트
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum WatchEvent {
+    /// 브챗 실행을 기다리는 중
+    WaitingGame,
+    /// 읽기 시작한 로그 파일 경로
+    LogOpened(String),
+    LogMissing,
+    GameClosed,
+    /// 새 인스턴스에 들어감
+    RoomChanged,
+    Joined(String),
+    Left(String),
+    /// 유저 확인 진행 상황
+    Checking { name: String, message: String },
+    /// 리퍼 스토어 확인까지 남은 시간
+    Waiting { name: String, elapsed: u64, total: u64 },
+    /// 리퍼 스토어 확인 결과. 뜯겼으면 뜯긴 아바타 이름과 해당 유저의 새 감지 횟수
+    Checked { name: String, avatars: Vec<String>, count: Option<u32> },
+    /// 수동으로 요청한 재검사 결과. 새로 뜯긴 아바타 이름
+    Rescanned { avatars: Vec<String> },
+    /// 감지 횟수가 높은 유저가 입장함
+    SuspectJoined { name: String, score: u32 },
+    /// 알림을 보내지 못한 곳의 오류
+    AlertFailed(String),
+}
+
+impl WatchEvent {
+    /// 기록 파일에 남길 한 줄. 진행 상황처럼 자주 오는 이벤트는 None
+    pub fn message(&self) -> Option<String> {
+        match self {
+            WatchEvent::WaitingGame => Some(tr!("watch.waiting_game")),
+            WatchEvent::LogOpened(path) => Some(tr!("watch.log_path", path)),
+            WatchEvent::LogMissing => Some(tr!("watch.log_missing_short")),
+            WatchEvent::GameClosed => Some(tr!("watch.game_closed")),
+            WatchEvent::RoomChanged => Some(tr!("watch.room_changed")),
+            WatchEvent::Joined(name) => Some(tr!("watch.joined", name)),
+            WatchEvent::Left(name) => Some(tr!("watch.left", name)),
+            WatchEvent::Checking { .. } | WatchEvent::Waiting { .. } => None,
+            WatchEvent::Checked { name, avatars, count } => count.map(|count| tr!("watch.leaked_on_join", name, avatars.join(", "), count)),
+            WatchEvent::Rescanned { avatars } if avatars.is_empty() => Some(tr!("watch.rescan_clean")),
+            WatchEvent::Rescanned { avatars } => Some(tr!("watch.rescan_leaked", avatars.join(", "))),
+            WatchEvent::SuspectJoined { name, score } => Some(tr!("watch.suspect_joined", score, name)),
+            WatchEvent::AlertFailed(error) => Some(tr!("watch.alert_failed", error)),
+        }
+    }
+}
+
+/// 감시 스레드와 화면이 같이 쓰는 상태
+#[derive(Clone)]
+pub struct WatchControl {
+    pub events: Sender<WatchEvent>,
+    pub muted: Arc<AtomicBool>,
+    /// 일시 정지 중에는 로그만 읽고 입장한 유저를 확인하지 않는다
+    pub paused: Arc<AtomicBool>,
+    pub alerter: Arc<Alerter>,
+}
+
+impl WatchControl {
+    fn send(&self, event: WatchEvent) {
+        let _ = self.events.send(event);
+    }
+
+    /// 소리 재생이 끝날 때 까지 기다리지 않도록 따로 스레드에서 보낸다
+    fn alert(&self, alert: Alert) {
+        let control = self.clone();
+        thread::spawn(move || {
+            for error in control.alerter.send(&alert, control.muted.load(Ordering::Relaxed)) {
+                control.send(WatchEvent::AlertFailed(error));
+            }
+        });
+    }
+
+    /// 입장 대기 없이 바로 리퍼 스토어 재검사
+    pub fn rescan(&self) {
+        let control = self.clone();
+        thread::spawn(move || {
+            let leaked = check_all_accounts();
+            let avatars = leaked.avatars;
+            if !avatars.is_empty() {
+                let alert = Alert::new(Severity::leak(leaked.again), tr!("alert.leak_title"), tr!("watch.rescan_leaked", avatars.join(", ")));
+                control.alert(alert.with_leak(LeakInfo { avatars: avatars.clone(), suspects: vec![], location: String::new() }));
+            }
+            control.send(WatchEvent::Rescanned { avatars });
+        });
+    }
+}
+
+pub fn is_process_running(target_process_name: &str) -> bool {
+    let snapshot = unsafe { CreateToolhelp32Snapshot(0x00000002, 0) };
+
+    if snapshot != ptr::null_mut() {
+        let mut entry: PROCESSENTRY32 = PROCESSENTRY32 {
+            dwSize: std::mem::size_of::<PROCESSENTRY32>() as DWORD,
+            cntUsage: 0,
+            th32ProcessID: 0,
+            th32DefaultHeapID: 0,
+            th32ModuleID: 0,
+            cntThreads: 0,
+            th32ParentProcessID: 0,
+            pcPriClassBase: 0,
+            dwFlags: 0,
+            szExeFile: [0; MAX_PATH],
+        };
+
+        if unsafe { Process32First(snapshot, &mut entry) } != 0 {
+            loop {
+                let process_name = entry.szExeFile.iter()
+                    .take_while(|&&c| c != 0)
+                    .map(|&c| c as u8 as char)
+                    .collect::<String>();
+
+                if process_name.to_lowercase() == target_process_name.to_lowercase() {
+                    return true;
+                }
+
+                if unsafe { Process32Next(snapshot, &mut entry) } == 0 {
+                    break;
+                }
+            }
+        }
+
+        unsafe { winapi::um::handleapi::CloseHandle(snapshot) };
+    }
+
+    false
+}
+
+/// 가장 최근 output_log 파일 찾기
+fn find_log_file() -> Option<String> {
+    let dir_path = config().paths.vrchat_log_dir.clone();
+    let specific_word = "output_log";
+    let mut waiting_count = 0;
+
+    loop {
+        thread::sleep(Duration::from_secs(1));
+        // 설정된 시간 동안 로그 읽기
+        if waiting_count == config().timing.log_wait {
+            return None;
+        } else {
+            waiting_count += 1;
+        }
+
+        let mut matching_files: Vec<fs::DirEntry> = Vec::new();
+        let entries = fs::read_dir(dir_path.clone()).unwrap();
+
+        for entry in entries {
+            let entry = entry.unwrap();
+            let path = entry.path();
+            let file_name = path.file_name().unwrap().to_string_lossy().to_lowercase();
+
+            if file_name.contains(specific_word) {
+                matching_files.push(entry);
+            }
+        }
+
+        matching_files.sort_by(|a, b| {
+            let a_metadata = a.metadata().unwrap();
+            let b_metadata = b.metadata().unwrap();
+            b_metadata.modified().unwrap().cmp(&a_metadata.modified().unwrap())
+        });
+
+        // 이전 로그는 ingest-logs 와 근거 확인에서 다시 읽으므로 지우지 않고, 가장 최근 로그만 연다
+        if let Some(file) = matching_files.first() {
+            return Some(file.path().to_string_lossy().into_owned());
+        }
+    }
+}
+
+/// 로그의 이름과 usr_ ID 로 감지 기록의 이름 찾기. ID 가 같으면 이름을 바꾼 유저도 같은 기록으로 본다
+fn resolve_name(display_name: &str, user_id: &str) -> String {
+    if user_id.is_empty() {
+        return display_name.to_string();
+    }
+    let names: Vec<String> = get_user().into_iter().filter(|a| a.user_id == user_id).map(|a| a.display_name).collect();
+    let rippers = get_ripper();
+    if rippers.iter().any(|a| a.name == display_name) {
+        return display_name.to_string();
+    }
+    rippers.into_iter().map(|a| a.name).find(|a| names.contains(a)).unwrap_or_else(|| display_name.to_string())
+}
+
+/// 처음 보는 유저면 user_id 를 저장. 로그에 usr_ ID 가 없으면 브챗 서버에서 불러온다
+fn register_user(target_name: &str, user_id: &str, control: &WatchControl) {
+    let mut user_list = get_user();
+    let exists = user_list.iter().any(|a| target_name == a.display_name && (user_id.is_empty() || a.user_id == user_id));
+    if exists {
+        control.send(WatchEvent::Checking { name: target_name.to_string(), message: tr!("watch.already_registered") });
+        return;
+    }
+    if !user_id.is_empty() {
+        user_list.push(UserData { created_at: vrcx_time(now_millis()), display_name: target_name.to_string(), user_id: user_id.to_string() });
+        set_user(user_list);
+        return;
+    }
+
+    control.send(WatchEvent::Checking { name: target_name.to_string(), message: tr!("watch.searching_server") });
+    let json = get_info_from_server(target_name.to_string(), &ProgressBar::hidden());
+
+    let conn = history::open();
+
+    let mut select_query = conn.prepare(&format!("SELECT created_at FROM gamelog_join_leave WHERE display_name = {}", json[0]["displayName"])).expect(&tr!("db.query_error"));
+    let result = select_query.query_map([], |row| {
+        Ok(UserData {
+            created_at: row.get(0).expect(&tr!("db.read_created_at")),
+            display_name: json[0]["displayName"].to_string().replace("\"", ""),
+            user_id: json[0]["id"].to_string().replace("\"", ""),
+        })
+    }).expect(&tr!("db.execute_error"));
+
+    for data in result {
+        user_list.push(data.expect(&tr!("db.row_error")));
+        break;
+    }
+
+    set_user(user_list);
+}
+
+// 의심 유저 알림에 넣을 최근 근거 개수
+const EVIDENCE_SUMMARY: usize = 3;
+
+/// 감지 횟수가 설정 값 이상인 유저가 들어오면 뜯기기 전에 미리 알림
+fn warn_if_suspect(target_name: &str, location: &str, control: &WatchControl) {
+    let threshold = config().alerts.suspect_threshold;
+    let Some(ripper) = get_ripper().into_iter().find(|a| a.name == target_name) else { return };
+    // 직접 확인한 유저는 감지 횟수와 상관없이 알리고, 아니라고 판단한 유저는 새 근거가 생기기 전까지 알리지 않는다
+    let over_threshold = threshold != 0 && ripper.count >= threshold;
+    if !(over_threshold || ripper.is_flagged()) || ripper.is_cleared() {
+        return;
+    }
+    if Allowlist::load().contains(target_name) {
+        return;
+    }
+
+    let mut events = ripper.events.clone();
+    events.sort_by_key(|a| Reverse(a.leak_time));
+    let mut evidence: Vec<String> = ripper.verdict.iter().map(|a| tr!("watch.verdict_row", a.kind.label(), a.note)).collect();
+    evidence.extend(events.iter().take(EVIDENCE_SUMMARY)
+        .map(|a| tr!("watch.evidence_row", format_time(a.leak_time), a.avatar_name, format!("{:+}", a.delta))));
+
+    let alert = Alert::new(Severity::SuspectJoined, tr!("alert.suspect_title"), tr!("alert.suspect_message", ripper.count, target_name));
+    control.alert(alert.with_suspect(SuspectInfo { name: target_name.to_string(), score: ripper.count, evidence, location: location.to_string() }));
+    control.send(WatchEvent::SuspectJoined { name: target_name.to_string(), score: ripper.count });
+}
+
+/// 입장 후 일정 시간 기다렸다가 리퍼 스토어 확인
+fn check_after_join(target_name: String, location: String, control: WatchControl) {
+    let joined_at = now_millis();
+    thread::spawn(move || {
+        let total = config().timing.join_check_delay;
+        for time in 1..=total {
+            thread::sleep(Duration::from_secs(1));
+            control.send(WatchEvent::Waiting { name: target_name.clone(), elapsed: time, total });
+        }
+
+        let mut count = None;
+        let leaked = check_all_accounts();
+        let avatars = leaked.avatars;
+        if !avatars.is_empty() {
+            let score = credit_live(&target_name, &avatars, joined_at, &location);
+            // 허용된 유저도 감지 근거는 남기지만, 알림에는 뜯긴 아바타만 넣는다
+            if Allowlist::load().contains(&target_name) {
+                let alert = Alert::new(Severity::leak(leaked.again), tr!("alert.leak_title"), tr!("watch.rescan_leaked", avatars.join(", ")));
+                control.alert(alert.with_leak(LeakInfo { avatars: avatars.clone(), suspects: vec![], location: location.clone() }));
+                control.send(WatchEvent::Checked { name: target_name, avatars, count: None });
+                return;
+            }
+            let alert = Alert::new(Severity::leak(leaked.again), tr!("alert.leak_title"), tr!("watch.leaked_on_join", target_name, avatars.join(", "), score));
+            control.alert(alert.with_leak(LeakInfo { avatars: avatars.clone(), suspects: vec![(target_name.clone(), score)], location: location.clone() }));
+            count = Some(score);
+        }
+        control.send(WatchEvent::Checked { name: target_name, avatars, count });
+    });
+}
+
+/// 인터넷이 끊겨 보내지 못한 알림을 설정한 간격마다 다시 보낸다
+fn spawn_flusher(control: WatchControl) {
+    let interval = config().timing.alert_retry;
+    thread::spawn(move || loop {
+        thread::sleep(Duration::from_secs(interval));
+        for error in control.alerter.flush() {
+            debug!("alert flush failed error={}", error);
+        }
+    });
+}
+
+/// 브챗 로그를 읽으며 이벤트를 보내는 감시 스레드 시작
+pub fn spawn(control: WatchControl) -> JoinHandle<()> {
+    spawn_flusher(control.clone());
+    thread::spawn(move || {
+        let owner_pattern = worn::owner_pattern();
+        let switch_pattern = worn::switch_pattern();
+        // 이름에 공백이나 기호가 들어갈 수 있으므로 줄 끝까지 읽고, 뒤에 붙은 usr_ ID 는 따로 읽는다
+        let join_pattern = Regex::new(r"OnPlayerJoined\s+(.+?)(?:\s+\((usr_[^)]+)\))?\s*$").expect(&tr!("common.regex_error"));
+        let left_pattern = Regex::new(r"OnPlayerLeft\s+(.+?)(?:\s+\((usr_[^)]+)\))?\s*$").expect(&tr!("common.regex_error"));
+        let room_pattern = Regex::new(r"\[Behaviour\] (Entering Room|Joining or Creating Room):").expect(&tr!("common.regex_error"));
+        let location_pattern = Regex::new(r"\[Behaviour\] Joining (wrld_\S+)").expect(&tr!("common.regex_error"));
+
+        loop {
+            control.send(WatchEvent::WaitingGame);
+            loop {
+                if is_process_running("VRChat.exe") {
+                    break;
+                }
+
+                thread::sleep(Duration::from_secs(config().timing.process_poll));
+            }
+
+            let Some(path) = find_log_file() else {
+                control.send(WatchEvent::LogMissing);
+                continue;
+            };
+            info!("vrchat log opened path={}", path);
+            // 이전에 비정상 종료되어 끝나지 않은 기록 정리
+            worn::record_end(now_millis());
+            control.send(WatchEvent::LogOpened(path.clone()));
+
+            let mut file_size = 0;
+            let mut owner = String::new();
+            let mut location = String::new();
+
+            loop {
+                thread::sleep(Duration::from_millis(10));
+
+                if !is_process_running("VRChat.exe") {
+                    worn::record_end(now_millis());
+                    control.send(WatchEvent::GameClosed);
+                    break;
+                }
+
+                let mut file = File::open(path.clone()).expect(&tr!("watch.log_open_error"));
+                let current_size = file.metadata().expect(&tr!("watch.log_metadata_error")).len();
+                if current_size > file_size {
+                    file.seek(SeekFrom::Start(file_size)).expect(&tr!("watch.log_seek_error"));
+                    let new_content = io::BufReader::new(&file);
+                    for line in new_content.lines() {
+                        let Ok(line_str) = line else { continue };
+                        let line_str = line_str.trim();
+
+                        if let Some(captures) = owner_pattern.captures(line_str) {
+                            owner = captures[1].to_string();
+                        }
+
+                        if let Some(captures) = switch_pattern.captures(line_str) {
+                            if !owner.is_empty() && captures[1].trim() == owner {
+                                let time = log_line_time(line_str).unwrap_or_else(now_millis);
+                                debug!("avatar switched avatar={}", &captures[2]);
+                                worn::record_switch(captures[2].trim(), time);
+                            }
+                        }
+
+                        if let Some(captures) = location_pattern.captures(line_str) {
+                            location = captures[1].to_string();
+                        }
+
+                        if room_pattern.is_match(line_str) {
+                            control.send(WatchEvent::RoomChanged);
+                        }
+
+                        if let Some(captures) = left_pattern.captures(line_str) {
+                            control.send(WatchEvent::Left(captures[1].trim().to_string()));
+                        }
+
+                        if let Some(captures) = join_pattern.captures(line_str) {
+                            let display_name = captures[1].trim().to_string();
+                            let user_id = captures.get(2).map(|a| a.as_str().to_string()).unwrap_or_default();
+                            debug!("player joined name={} user_id={} location={}", display_name, user_id, location);
+                            control.send(WatchEvent::Joined(display_name.clone()));
+                            if display_name != owner && !control.paused.load(Ordering::Relaxed) {
+                                let target_name = resolve_name(&display_name, &user_id);
+                                warn_if_suspect(&target_name, &location, &control);
+                                control.send(WatchEvent::Checking { name: target_name.clone(), message: tr!("watch.checking_user") });
+                                register_user(&display_name, &user_id, &control);
+                                control.send(WatchEvent::Checking { name: target_name.clone(), message: tr!("watch.checking") });
+                                check_after_join(target_name, location.clone(), control.clone());
+                            }
+                        }
+                    }
+                    file_size = current_size;
+                }
+            }
+        }
+    })
+}
+
+pub fn new_control() -> (WatchControl, Receiver<WatchEvent>) {
+    let (sender, receiver) = channel();
+    let control = WatchControl {
+        events: sender,
+        muted: Arc::new(AtomicBool::new(false)),
+        paused: Arc::new(AtomicBool::new(false)),
+        alerter: Arc::new(Alerter::from_config()),
+    };
+    (control, receiver)
+}
+
+/// 기존 콘솔 방식으로 감시 이벤트 출력
+pub fn run_console(no_input: bool) {
+    let (control, receiver) = new_control();
+    let handle = spawn(control);
+
+    thread::spawn(move || {
+        let m = MultiProgress::new();
+        let style = ProgressStyle::with_template("{spinner} {wide_msg}").unwrap().tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈ ");
+        let mut bars: HashMap<String, ProgressBar> = HashMap::new();
+
+        for event in receiver {
+            match event {
+                WatchEvent::LogOpened(path) => println!("{}", tr!("watch.log_path", path)),
+                WatchEvent::LogMissing => println!("{}", tr!("watch.log_missing")),
+                WatchEvent::GameClosed => println!("{}", tr!("watch.game_closed")),
+                WatchEvent::Checking { name, message } => {
+                    let pb = bars.entry(name.clone()).or_insert_with(|| {
+                        let pb = m.add(ProgressBar::new(1));
+                        pb.set_style(style.clone());
+                        pb
+                    });
+                    pb.set_message(format!("{} - {}", name, message));
+                }
+                WatchEvent::Waiting { name, elapsed, .. } => {
+                    if let Some(pb) = bars.get(&name) {
+                        pb.set_message(tr!("watch.waiting_row", name, elapsed));
+                    }
+                }
+                WatchEvent::Checked { name, avatars, count } => {
+                    if let Some(pb) = bars.remove(&name) {
+                        pb.finish_and_clear();
+                    }
+                    if let Some(count) = count {
+                        println!("{}", tr!("watch.avatars_leaked", avatars.join(", ")));
+                        println!("{}", tr!("watch.leaked_user", name, count));
+                    }
+                }
+                WatchEvent::Rescanned { avatars } => {
+                    if !avatars.is_empty() {
+                        println!("{}", tr!("watch.rescan_leaked", avatars.join(", ")));
+                    }
+                }
+                WatchEvent::SuspectJoined { name, score } => println!("{}", tr!("watch.suspect_joined", score, name)),
+                WatchEvent::AlertFailed(error) => println!("{}", tr!("watch.alert_failed", error)),
+                WatchEvent::WaitingGame | WatchEvent::RoomChanged | WatchEvent::Joined(_) | WatchEvent::Left(_) => {}
+            }
+        }
+    });
+
+    if no_input {
+        handle.join().expect(&tr!("watch.thread_error"));
+        return;
+    }
+
+    println!("{}", tr!("watch.close_hint"));
+    println!("{}", tr!("watch.count_hint"));
+
+    loop {
+        let command: String = read!();
+
+        if command == "a" {
+            print_rippers(1);
+        }
+    }
+}
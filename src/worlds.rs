@@ -0,0 +1,154 @@
+use std::fs;
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::config::config;
+use crate::library::{parse_vrcx_time, vrcx_time};
+
+pub const LEAKS_FILE: &str = "leaks.json";
+
+/// VRCX gamelog_location 테이블에서 찾은, 내가 있던 인스턴스
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Visit {
+    pub location: String,
+    pub world_id: String,
+    pub world_name: String,
+    /// 인스턴스에 들어간 시간 (UTC 밀리초). VRCX 기록이 없으면 None
+    pub entered_at: Option<i64>,
+}
+
+impl Visit {
+    /// 브챗 로그의 위치 (wrld_...:인스턴스) 만 알 때
+    pub fn from_location(location: &str) -> Visit {
+        Visit {
+            location: location.to_string(),
+            world_id: location.split(':').next().unwrap_or_default().to_string(),
+            world_name: String::new(),
+            entered_at: None,
+        }
+    }
+}
+
+/// 뜯긴 시간 하나와 그 때 내가 있던 인스턴스, 같이 있던 유저들
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeakRecord {
+    pub avatar_id: String,
+    pub avatar_name: String,
+    /// 뜯긴 시간 (UTC 밀리초)
+    pub leak_time: i64,
+    /// 인스턴스 기록이 없으면 None
+    pub visit: Option<Visit>,
+    /// 같은 인스턴스에서 감지 횟수를 받은 유저
+    pub players: Vec<String>,
+}
+
+/// 주어진 시간에 내가 있던 인스턴스. 그 전에 들어간 기록 중 가장 최근 것
+pub fn visit_at(conn: &Connection, time: i64) -> Option<Visit> {
+    let mut stmt = conn.prepare("SELECT created_at, location, world_id, world_name FROM gamelog_location WHERE created_at <= ?1 ORDER BY created_at DESC LIMIT 1").expect(&tr!("db.query_error"));
+    let row = stmt.query_row([vrcx_time(time)], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+            row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+            row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+        ))
+    }).optional().expect(&tr!("db.execute_error"))?;
+    let (created_at, location, world_id, world_name) = row;
+    Some(Visit { location, world_id, world_name, entered_at: parse_vrcx_time(&created_at) })
+}
+
+pub fn get_leaks() -> Vec<LeakRecord> {
+    fs::read_to_string(config().data_path(LEAKS_FILE)).ok()
+        .and_then(|a| serde_json::from_str(&a).ok())
+        .unwrap_or_default()
+}
+
+/// 같은 뜯김은 한번만 기록하고, 다시 계산했으면 유저 목록을 합친다
+pub fn record_leak(leak: LeakRecord) {
+    let mut list = get_leaks();
+    match list.iter_mut().find(|a| a.avatar_id == leak.avatar_id && a.avatar_name == leak.avatar_name && a.leak_time == leak.leak_time) {
+        Some(found) => {
+            for player in leak.players {
+                if !found.players.contains(&player) {
+                    found.players.push(player);
+                }
+            }
+            if found.visit.is_none() {
+                found.visit = leak.visit;
+            }
+        }
+        None => list.push(leak),
+    }
+    list.sort_by_key(|a| a.leak_time);
+    let text = serde_json::to_string(&list).expect(&tr!("json.serialize_error"));
+    fs::write(config().data_path(LEAKS_FILE), text).expect(&tr!("io.write_error"));
+}
+
+/// 인스턴스 하나에서 일어난 뜯김들
+#[derive(Debug, Serialize)]
+pub struct InstanceSummary {
+    pub location: String,
+    pub leaks: Vec<(String, i64)>,
+    pub players: Vec<String>,
+}
+
+/// 월드 하나에서 일어난 뜯김 모음
+#[derive(Debug, Serialize)]
+pub struct WorldSummary {
+    pub world_id: String,
+    pub world_name: String,
+    pub leaks: usize,
+    pub instances: Vec<InstanceSummary>,
+    /// 여러 뜯김에 같이 있던 순서로 정렬한 유저와 횟수
+    pub players: Vec<(String, usize)>,
+}
+
+/// 뜯김이 많은 월드 순서로 정리. 인스턴스 기록이 없는 뜯김은 world_id 가 빈 항목으로 모은다
+pub fn summarize(leaks: &[LeakRecord]) -> Vec<WorldSummary> {
+    let mut worlds: Vec<WorldSummary> = vec![];
+    for leak in leaks {
+        let visit = leak.visit.clone().unwrap_or_default();
+        let index = match worlds.iter().position(|a| a.world_id == visit.world_id) {
+            Some(index) => index,
+            None => {
+                worlds.push(WorldSummary { world_id: visit.world_id.clone(), world_name: visit.world_name.clone(), leaks: 0, instances: vec![], players: vec![] });
+                worlds.len() - 1
+            }
+        };
+        let world = &mut worlds[index];
+        world.leaks += 1;
+        // 실시간 감시로 기록한 뜯김은 월드 이름이 없다
+        if world.world_name.is_empty() {
+            world.world_name = visit.world_name.clone();
+        }
+        for player in &leak.players {
+            match world.players.iter_mut().find(|a| a.0 == *player) {
+                Some(found) => found.1 += 1,
+                None => world.players.push((player.clone(), 1)),
+            }
+        }
+
+        match world.instances.iter_mut().find(|a| a.location == visit.location) {
+            Some(instance) => {
+                instance.leaks.push((leak.avatar_name.clone(), leak.leak_time));
+                for player in &leak.players {
+                    if !instance.players.contains(player) {
+                        instance.players.push(player.clone());
+                    }
+                }
+            }
+            None => world.instances.push(InstanceSummary {
+                location: visit.location.clone(),
+                leaks: vec![(leak.avatar_name.clone(), leak.leak_time)],
+                players: leak.players.clone(),
+            }),
+        }
+    }
+
+    for world in worlds.iter_mut() {
+        world.players.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    }
+    worlds.sort_by(|a, b| b.leaks.cmp(&a.leaks).then(a.world_name.cmp(&b.world_name)));
+    worlds
+}
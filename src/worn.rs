@@ -0,0 +1,166 @@
+use std::fs;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::bundle::log_line_time;
+use crate::config::config;
+
+const WORN_FILE: &str = "worn.json";
+
+/// 내가 아바타 하나를 입고 있던 시간
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WornInterval {
+    pub avatar_name: String,
+    /// 갈아입은 시간 (UTC 밀리초)
+    pub start: i64,
+    /// 다른 아바타로 바꾸거나 게임을 끈 시간. 아직 입고 있으면 None
+    pub end: Option<i64>,
+}
+
+impl WornInterval {
+    /// start ~ end 사이에 조금이라도 입고 있었는지
+    pub fn overlaps(&self, start: i64, end: i64) -> bool {
+        self.start <= end && self.end.is_none_or(|a| a >= start)
+    }
+}
+
+pub fn owner_pattern() -> Regex {
+    Regex::new(r"User Authenticated: (.+?) \(usr_").expect(&tr!("common.regex_error"))
+}
+
+pub fn switch_pattern() -> Regex {
+    Regex::new(r"\[Behaviour\] Switching (.+) to avatar (.+)$").expect(&tr!("common.regex_error"))
+}
+
+/// 리퍼 스토어와 브챗의 아바타 이름은 대소문자나 앞뒤 공백이 다를 수 있다
+fn same_avatar(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+pub fn get_worn() -> Vec<WornInterval> {
+    fs::read_to_string(config().data_path(WORN_FILE)).ok()
+        .and_then(|a| serde_json::from_str(&a).ok())
+        .unwrap_or_default()
+}
+
+fn set_worn(list: &[WornInterval]) {
+    let text = serde_json::to_string(list).expect(&tr!("json.serialize_error"));
+    fs::write(config().data_path(WORN_FILE), text).expect(&tr!("io.write_error"));
+}
+
+/// 같은 시간에 시작한 기록이 있으면 끝난 시간만 채운다. 같은 로그를 여러번 읽어도 중복되지 않는다
+fn merge(list: &mut Vec<WornInterval>, interval: WornInterval) {
+    match list.iter_mut().find(|a| a.start == interval.start && a.avatar_name == interval.avatar_name) {
+        Some(found) => {
+            if found.end.is_none() {
+                found.end = interval.end;
+            }
+        }
+        None => list.push(interval),
+    }
+}
+
+/// 실시간 감시 중 내가 아바타를 갈아입음
+pub fn record_switch(avatar_name: &str, time: i64) {
+    let mut list = get_worn();
+    for interval in list.iter_mut().filter(|a| a.end.is_none() && a.start < time) {
+        interval.end = Some(time);
+    }
+    merge(&mut list, WornInterval { avatar_name: avatar_name.to_string(), start: time, end: None });
+    list.sort_by_key(|a| a.start);
+    set_worn(&list);
+}
+
+/// 게임이 꺼져서 입고 있던 아바타도 끝남
+pub fn record_end(time: i64) {
+    let mut list = get_worn();
+    let mut changed = false;
+    for interval in list.iter_mut().filter(|a| a.end.is_none()) {
+        interval.end = Some(time.max(interval.start));
+        changed = true;
+    }
+    if changed {
+        set_worn(&list);
+    }
+}
+
+/// 로그 파일 하나에서 내가 아바타를 입고 있던 시간 찾기. 파일의 마지막 줄에서 끝난 것으로 본다
+pub fn parse_log(reader: impl BufRead) -> Vec<WornInterval> {
+    let owner_pattern = owner_pattern();
+    let switch_pattern = switch_pattern();
+    let mut owner = String::new();
+    let mut intervals: Vec<WornInterval> = vec![];
+    let mut last_time = None;
+
+    for line in reader.lines().map_while(Result::ok) {
+        let Some(time) = log_line_time(&line) else { continue };
+        last_time = Some(time);
+        if let Some(captures) = owner_pattern.captures(&line) {
+            owner = captures[1].to_string();
+            continue;
+        }
+        let Some(captures) = switch_pattern.captures(&line) else { continue };
+        if owner.is_empty() || captures[1].trim() != owner {
+            continue;
+        }
+        if let Some(previous) = intervals.last_mut() {
+            previous.end = Some(time);
+        }
+        intervals.push(WornInterval { avatar_name: captures[2].trim().to_string(), start: time, end: None });
+    }
+
+    if let (Some(previous), Some(time)) = (intervals.last_mut(), last_time) {
+        previous.end = Some(time);
+    }
+    intervals
+}
+
+/// 보관해둔 로그에서 읽은 시간을 기록에 합치기
+pub fn add_intervals(intervals: Vec<WornInterval>) {
+    if intervals.is_empty() {
+        return;
+    }
+    let mut list = get_worn();
+    for interval in intervals {
+        merge(&mut list, interval);
+    }
+    list.sort_by_key(|a| a.start);
+    set_worn(&list);
+}
+
+/// 남아있는 브챗 로그에서 아바타를 입고 있던 시간을 읽어서 기록에 합치기
+pub fn scan_logs() {
+    let Ok(entries) = fs::read_dir(&config().paths.vrchat_log_dir) else { return };
+    let mut list = get_worn();
+    for entry in entries.flatten() {
+        if !entry.file_name().to_string_lossy().contains("output_log") {
+            continue;
+        }
+        let Ok(file) = File::open(entry.path()) else { continue };
+        for interval in parse_log(BufReader::new(file)) {
+            merge(&mut list, interval);
+        }
+    }
+    list.sort_by_key(|a| a.start);
+    set_worn(&list);
+}
+
+/// 주어진 시간 범위에 어떤 아바타를 입고 있었는지 기록이 있으면, 그 중 해당 아바타를 입고 있던 시간들.
+/// 기록이 전혀 없는 시간이면 None
+pub fn worn_during(avatar_name: &str, start: i64, end: i64) -> Option<Vec<WornInterval>> {
+    let mut list = get_worn();
+    // 끝나지 않은 기록은 가장 최근 것만 지금까지 입고 있는 것으로 보고, 나머지는 다음 기록 전까지로 본다
+    for i in 1..list.len() {
+        if list[i - 1].end.is_none() {
+            list[i - 1].end = Some(list[i].start);
+        }
+    }
+    let overlapping: Vec<WornInterval> = list.into_iter().filter(|a| a.overlaps(start, end)).collect();
+    if overlapping.is_empty() {
+        return None;
+    }
+    Some(overlapping.into_iter().filter(|a| same_avatar(&a.avatar_name, avatar_name)).collect())
+}